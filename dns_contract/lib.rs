@@ -3,18 +3,49 @@
 #[ink::contract]
 mod dns_contract {
 
+    use ink::env::hash::Blake2x256;
     use ink::prelude::{string::String, vec::Vec};
     use ink::storage::Mapping;
 
     // type for domain id
     pub type DomainNameId = i32;
 
+    // type for auction id
+    pub type AuctionId = i32;
+
+    // one registration year in milliseconds
+    const YEAR: Timestamp = 365 * 24 * 60 * 60 * 1000;
+
+    // period after expiry in which only the previous owner can renew
+    const GRACE_PERIOD: Timestamp = 90 * 24 * 60 * 60 * 1000;
+
+    // a commitment can be revealed once it is this old, unless deployed with another age
+    const MIN_COMMITMENT_AGE: Timestamp = 60 * 1000;
+
+    // a commitment older than this is stale and can be cleaned by anyone
+    const MAX_COMMITMENT_AGE: Timestamp = 24 * 60 * 60 * 1000;
+
+    // sealed bids are revealed within this period after the auction end time
+    const REVEAL_PERIOD: Timestamp = 24 * 60 * 60 * 1000;
+
+    // number of entries returned by a paginated query at most
+    const MAX_PAGE_SIZE: u32 = 50;
+
+    // every top level name is registered under this suffix
+    const TLD: &str = "dot";
+
+    // length limits of a top level label, any label and a full name
+    const MIN_NAME_LENGTH: usize = 3;
+    const MAX_LABEL_LENGTH: usize = 63;
+    const MAX_NAME_LENGTH: usize = 253;
+
     // offer state for domain name
-    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq)]
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq, Clone)]
     #[cfg_attr(
         feature = "std",
         derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
     )]
+    #[allow(clippy::enum_variant_names)]
     pub enum State {
         NotOffering,
         PrivateOffering,
@@ -32,6 +63,52 @@ mod dns_contract {
         offer_state: State,
         offer_price: u128,
         default_address: AccountId,
+        expires_at: Timestamp,
+        allowed_buyer: Option<AccountId>,
+        parent: Option<DomainNameId>,
+        locked: bool,
+    }
+
+    // bidding mode of an auction
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub enum AuctionKind {
+        Open,
+        Sealed,
+    }
+
+    // struct for name auction
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Auction {
+        name: String,
+        kind: AuctionKind,
+        reserve_price: Balance,
+        end_time: Timestamp,
+        highest_bidder: Option<AccountId>,
+        highest_bid: Balance,
+        settled: bool,
+    }
+
+    // registration prices set by the contract owner
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct FeeSchedule {
+        // yearly price by label length starting at one character,
+        // the last entry applies to all longer labels
+        length_prices: Vec<Balance>,
+        renewal_price: Balance,
+        // share of every sale kept by the treasury, in basis points
+        sale_fee_bps: u16,
     }
 
     // Default implementation for Domain name
@@ -42,6 +119,10 @@ mod dns_contract {
                 offer_state: State::NotOffering,
                 offer_price: Default::default(),
                 default_address: zero_address(),
+                expires_at: Default::default(),
+                allowed_buyer: None,
+                parent: None,
+                locked: false,
             }
         }
     }
@@ -51,13 +132,91 @@ mod dns_contract {
         [0u8; 32].into()
     }
 
+    // lowercase a label and check it only uses `a-z`, `0-9` and inner hyphens
+    fn normalize_label(label: &str) -> Result<String, DNSError> {
+        if label.is_empty() {
+            return Err(DNSError::InvalidLabel);
+        }
+        if label.len() > MAX_LABEL_LENGTH {
+            return Err(DNSError::NameTooLong);
+        }
+        if !label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-') {
+            return Err(DNSError::InvalidCharacter);
+        }
+        if label.starts_with('-') || label.ends_with('-') {
+            return Err(DNSError::InvalidHyphen);
+        }
+        Ok(label.to_ascii_lowercase())
+    }
+
+    // normalize a full name such as `api.myproject.dot`, label by label
+    fn normalize_name(name: &str) -> Result<String, DNSError> {
+        if name.is_empty() {
+            return Err(DNSError::EmptyName);
+        }
+        if name.len() > MAX_NAME_LENGTH {
+            return Err(DNSError::NameTooLong);
+        }
+
+        let mut labels = Vec::new();
+        for label in name.split('.') {
+            labels.push(normalize_label(label)?);
+        }
+        if labels.len() < 2 || labels.last().map(String::as_str) != Some(TLD) {
+            return Err(DNSError::InvalidTld);
+        }
+        Ok(labels.join("."))
+    }
+
+    // normalize a name that can be registered directly, i.e. `<label>.dot`
+    fn normalize_top_level_name(name: &str) -> Result<String, DNSError> {
+        let name = normalize_name(name)?;
+
+        let mut labels = name.split('.');
+        match (labels.next(), labels.next(), labels.next()) {
+            (Some(label), Some(_), None) if label.len() < MIN_NAME_LENGTH => {
+                Err(DNSError::NameTooShort)
+            }
+            (Some(_), Some(_), None) => Ok(name),
+            _ => Err(DNSError::NotATopLevelName),
+        }
+    }
+
     #[ink(storage)]
     pub struct DnsContract {
         owner: AccountId,
         owner_name_count: Mapping<AccountId, i32>,
+        // domains of an owner by position, and the position of each domain
+        owned_domains: Mapping<(AccountId, i32), DomainNameId>,
+        owned_index: Mapping<DomainNameId, i32>,
         domain_name: Mapping<DomainNameId, DomainName>,
         name_to_owner: Mapping<String, AccountId>,
+        name_to_id: Mapping<String, DomainNameId>,
         claimed: Mapping<DomainNameId, bool>,
+        // operators approved for all domains of an owner and approvals of single domains
+        operators: Mapping<(AccountId, AccountId), bool>,
+        token_approvals: Mapping<DomainNameId, AccountId>,
+        // resolver records
+        address_record: Mapping<DomainNameId, AccountId>,
+        text_record: Mapping<(DomainNameId, String), String>,
+        text_keys: Mapping<DomainNameId, Vec<String>>,
+        content_hash_record: Mapping<DomainNameId, Vec<u8>>,
+        // reverse record from an account to its primary domain name
+        primary_name: Mapping<AccountId, DomainNameId>,
+        fee_schedule: FeeSchedule,
+        treasury: Balance,
+        // subdomains by parent id and label
+        subdomains: Mapping<(DomainNameId, String), DomainNameId>,
+        subdomain_labels: Mapping<DomainNameId, Vec<String>>,
+        // registration commitments and the time they were made
+        commitments: Mapping<Hash, Timestamp>,
+        min_commitment_age: Timestamp,
+        // name auctions with escrowed bidder deposits and sealed bid commitments
+        auctions: Mapping<AuctionId, Auction>,
+        name_to_auction: Mapping<String, AuctionId>,
+        auction_deposits: Mapping<(AuctionId, AccountId), Balance>,
+        sealed_bids: Mapping<(AuctionId, AccountId), Hash>,
+        auction_id: AuctionId,
         no_of_claimed_names: i32,
         domain_name_id: i32,
     }
@@ -72,159 +231,1552 @@ mod dns_contract {
         SameOwner,
         NameAlreadyClaimed,
         DomainAlreadyOwned,
+        DomainNotFound,
+        NotOwnerOrOperator,
+        InsufficientPayment,
+        DomainExpired,
+        NotForSale,
+        NotAllowedBuyer,
+        MissingAllowedBuyer,
+        TransferFailed,
+        InvalidLabel,
+        NotASubdomain,
+        SubdomainLocked,
+        SubdomainNotRenewable,
+        EmptyName,
+        NameTooShort,
+        NameTooLong,
+        InvalidCharacter,
+        InvalidHyphen,
+        InvalidTld,
+        NotATopLevelName,
+        CommitmentExists,
+        CommitmentNotFound,
+        CommitmentTooNew,
+        CommitmentTooOld,
+        CommitmentNotStale,
+        NameInAuction,
+        AuctionNotFound,
+        AuctionEnded,
+        AuctionNotEnded,
+        AuctionSettled,
+        InvalidEndTime,
+        WrongAuctionKind,
+        BidTooLow,
+        InvalidBidReveal,
+        HighestBidderCannotWithdraw,
+        NothingToWithdraw,
+        InvalidFeeSchedule,
+        InsufficientTreasury,
+        InexactPayment,
+    }
+
+    // resolver record carried by `RecordUpdated`, an empty value means the record was removed
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq, Clone)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub enum Record {
+        Address(AccountId),
+        Text(String, String),
+        ContentHash(Vec<u8>),
     }
 
     // events message
     #[ink(event)]
     pub struct NewNameClaimed {
         #[ink(topic)]
-        address: AccountId,
+        name_id: DomainNameId,
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        owner: AccountId,
+        expires_at: Timestamp,
     }
 
     #[ink(event)]
     pub struct SetNewOwner {
         #[ink(topic)]
-        address: AccountId,
+        name_id: DomainNameId,
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        old_owner: AccountId,
+        #[ink(topic)]
+        new_owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct OfferChanged {
+        #[ink(topic)]
+        name_id: DomainNameId,
+        #[ink(topic)]
+        name: String,
+        offer_state: State,
+        offer_price: u128,
+        allowed_buyer: Option<AccountId>,
+    }
+
+    #[ink(event)]
+    pub struct DomainSold {
+        #[ink(topic)]
+        name_id: DomainNameId,
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        seller: AccountId,
+        #[ink(topic)]
+        buyer: AccountId,
+        price: Balance,
+    }
+
+    #[ink(event)]
+    pub struct RecordUpdated {
+        #[ink(topic)]
+        name_id: DomainNameId,
+        #[ink(topic)]
+        name: String,
+        record: Record,
+    }
+
+    #[ink(event)]
+    pub struct Renewed {
+        #[ink(topic)]
+        name_id: DomainNameId,
+        #[ink(topic)]
+        name: String,
+        expires_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct Expired {
+        #[ink(topic)]
+        name_id: DomainNameId,
+        #[ink(topic)]
+        name: String,
+        #[ink(topic)]
+        owner: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct AuctionStarted {
+        #[ink(topic)]
+        auction_id: AuctionId,
+        name: String,
+        end_time: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct BidPlaced {
+        #[ink(topic)]
+        auction_id: AuctionId,
+        #[ink(topic)]
+        bidder: AccountId,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct AuctionSettled {
+        #[ink(topic)]
+        auction_id: AuctionId,
+        #[ink(topic)]
+        winner: Option<AccountId>,
+        amount: Balance,
+    }
+
+    #[ink(event)]
+    pub struct FeeScheduleChanged {
+        length_prices: Vec<Balance>,
+        renewal_price: Balance,
+        sale_fee_bps: u16,
+    }
+
+    #[ink(event)]
+    pub struct Withdrawn {
+        #[ink(topic)]
+        to: AccountId,
+        amount: Balance,
+    }
+
+    // PSP34 token events
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        #[ink(topic)]
+        id: Id,
+    }
+
+    #[ink(event)]
+    pub struct Approval {
+        #[ink(topic)]
+        owner: AccountId,
+        #[ink(topic)]
+        operator: AccountId,
+        #[ink(topic)]
+        id: Option<Id>,
+        approved: bool,
+    }
+
+    /// PSP34 token id, domains are minted as `Id::U32` with their `DomainNameId`.
+    #[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum Id {
+        U8(u8),
+        U16(u16),
+        U32(u32),
+        U64(u64),
+        U128(u128),
+        Bytes(Vec<u8>),
+    }
+
+    impl From<DomainNameId> for Id {
+        fn from(name_id: DomainNameId) -> Self {
+            Id::U32(name_id as u32)
+        }
+    }
+
+    impl Id {
+        // the domain a token id refers to, any integer variant with the same value matches
+        fn name_id(&self) -> Option<DomainNameId> {
+            match self {
+                Id::U8(id) => Some(DomainNameId::from(*id)),
+                Id::U16(id) => Some(DomainNameId::from(*id)),
+                Id::U32(id) => DomainNameId::try_from(*id).ok(),
+                Id::U64(id) => DomainNameId::try_from(*id).ok(),
+                Id::U128(id) => DomainNameId::try_from(*id).ok(),
+                Id::Bytes(_) => None,
+            }
+        }
+    }
+
+    /// Errors that can occur upon calling the PSP34 interface of this contract.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum PSP34Error {
+        Custom(String),
+        SelfApprove,
+        NotApproved,
+        TokenExists,
+        TokenNotExists,
+        SafeTransferCheckFailed(String),
+    }
+
+    // PSP34 non-fungible token interface, every domain is a token with its `DomainNameId` as id
+    #[ink::trait_definition]
+    pub trait PSP34 {
+        #[ink(message)]
+        fn collection_id(&self) -> Id;
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u32;
+
+        #[ink(message)]
+        fn owner_of(&self, id: Id) -> Option<AccountId>;
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool;
+
+        #[ink(message)]
+        fn approve(
+            &mut self,
+            operator: AccountId,
+            id: Option<Id>,
+            approved: bool,
+        ) -> Result<(), PSP34Error>;
+
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, id: Id, data: Vec<u8>) -> Result<(), PSP34Error>;
+
+        #[ink(message)]
+        fn total_supply(&self) -> Balance;
     }
 
     impl DnsContract {
         #[ink(constructor)]
-        pub fn new() -> Self {
+        pub fn new(fee_per_year: Balance) -> Self {
+            Self::with_commitment_age(fee_per_year, MIN_COMMITMENT_AGE)
+        }
+
+        // a shorter commitment age weakens the front-running protection of commit and
+        // reveal, it is meant for test deployments
+        #[ink(constructor)]
+        pub fn with_commitment_age(fee_per_year: Balance, min_commitment_age: Timestamp) -> Self {
             Self {
                 owner: Self::env().caller(),
                 owner_name_count: Mapping::default(),
+                owned_domains: Mapping::default(),
+                owned_index: Mapping::default(),
                 domain_name: Mapping::default(),
                 name_to_owner: Mapping::default(),
+                name_to_id: Mapping::default(),
                 claimed: Mapping::default(),
+                operators: Mapping::default(),
+                token_approvals: Mapping::default(),
+                address_record: Mapping::default(),
+                text_record: Mapping::default(),
+                text_keys: Mapping::default(),
+                content_hash_record: Mapping::default(),
+                primary_name: Mapping::default(),
+                fee_schedule: FeeSchedule {
+                    length_prices: Vec::from([fee_per_year]),
+                    renewal_price: fee_per_year,
+                    sale_fee_bps: 0,
+                },
+                treasury: 0,
+                subdomains: Mapping::default(),
+                subdomain_labels: Mapping::default(),
+                commitments: Mapping::default(),
+                min_commitment_age,
+                auctions: Mapping::default(),
+                name_to_auction: Mapping::default(),
+                auction_deposits: Mapping::default(),
+                sealed_bids: Mapping::default(),
+                auction_id: 1,
                 no_of_claimed_names: Default::default(),
                 domain_name_id: 1,
             }
         }
 
+        // first registration step, commit to `make_commitment(name, secret, caller)`
         #[ink(message)]
-        pub fn create_new_dns(
+        pub fn commit(&mut self, commitment: Hash) -> Result<(), DNSError> {
+            let now = self.env().block_timestamp();
+
+            if let Some(committed_at) = self.commitments.get(commitment) {
+                if now <= committed_at.saturating_add(MAX_COMMITMENT_AGE) {
+                    return Err(DNSError::CommitmentExists);
+                }
+            }
+
+            self.commitments.insert(commitment, &now);
+            Ok(())
+        }
+
+        // second registration step, register the committed name for the caller
+        #[ink(message, payable)]
+        pub fn reveal(
             &mut self,
             name: String,
+            secret: Hash,
             offer_state: State,
             offer_price: u128,
         ) -> Result<(), DNSError> {
-            let name_id = self.next_domain_name_id();
-            let claimed = self.claimed.get(name_id).unwrap_or_default();
-            let caller = self.env().caller();
+            let commitment = self.make_commitment(name.clone(), secret, self.env().caller());
+            let committed_at = self
+                .commitments
+                .get(commitment)
+                .ok_or(DNSError::CommitmentNotFound)?;
+            let now = self.env().block_timestamp();
 
-            if self.name_to_owner.contains(&name) {
-                return Err(DNSError::DomainAlreadyOwned);
+            if now < committed_at.saturating_add(self.min_commitment_age) {
+                return Err(DNSError::CommitmentTooNew);
+            }
+            if now > committed_at.saturating_add(MAX_COMMITMENT_AGE) {
+                return Err(DNSError::CommitmentTooOld);
             }
 
-            // insert name to owner
-            self.name_to_owner.insert(&name, &caller);
+            self.commitments.remove(commitment);
+            self.create_new_dns(name, offer_state, offer_price)
+        }
 
-            // check name mustn't be already claimed
-            if claimed {
-                return Err(DNSError::NameAlreadyClaimed);
+        // remove a commitment that can no longer be revealed
+        #[ink(message)]
+        pub fn clean_commitment(&mut self, commitment: Hash) -> Result<(), DNSError> {
+            let committed_at = self
+                .commitments
+                .get(commitment)
+                .ok_or(DNSError::CommitmentNotFound)?;
+
+            if self.env().block_timestamp() <= committed_at.saturating_add(MAX_COMMITMENT_AGE) {
+                return Err(DNSError::CommitmentNotStale);
             }
 
-            let domain_name = DomainName {
-                name,
-                offer_state,
-                offer_price,
-                default_address: caller,
+            self.commitments.remove(commitment);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn make_commitment(&self, name: String, secret: Hash, owner: AccountId) -> Hash {
+            let mut output = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(&(name, secret, owner), &mut output);
+            output.into()
+        }
+
+        #[ink(message)]
+        pub fn get_commitment(&self, commitment: Hash) -> Option<Timestamp> {
+            self.commitments.get(commitment)
+        }
+
+        // put an unregistered or released name up for auction, only by the contract owner
+        #[ink(message)]
+        pub fn start_auction(
+            &mut self,
+            name: String,
+            kind: AuctionKind,
+            reserve_price: Balance,
+            end_time: Timestamp,
+        ) -> Result<AuctionId, DNSError> {
+            if self.env().caller() != self.owner {
+                return Err(DNSError::CallerIsNotOwner);
+            }
+
+            let name = normalize_top_level_name(&name)?;
+            if self.name_to_auction.contains(&name) {
+                return Err(DNSError::NameInAuction);
+            }
+            if !self.is_available(&name) {
+                return Err(DNSError::DomainAlreadyOwned);
+            }
+            if end_time <= self.env().block_timestamp() {
+                return Err(DNSError::InvalidEndTime);
+            }
+
+            let auction_id = self.next_auction_id();
+            let auction = Auction {
+                name: name.clone(),
+                kind,
+                reserve_price,
+                end_time,
+                highest_bidder: None,
+                highest_bid: 0,
+                settled: false,
             };
 
-            self.domain_name.insert(name_id, &domain_name);
-            self.claimed.insert(name_id, &true);
-            self.no_of_claimed_names += 1;
+            self.auctions.insert(auction_id, &auction);
+            self.name_to_auction.insert(&name, &auction_id);
+
+            self.env().emit_event(AuctionStarted {
+                auction_id,
+                name,
+                end_time,
+            });
+            Ok(auction_id)
+        }
+
+        // raise caller's open bid by the transferred value, which stays escrowed
+        #[ink(message, payable)]
+        pub fn bid(&mut self, auction_id: AuctionId) -> Result<(), DNSError> {
+            let mut auction = self.get_running_auction(auction_id, AuctionKind::Open)?;
+            let caller = self.env().caller();
+
+            let amount = self
+                .auction_deposits
+                .get((auction_id, caller))
+                .unwrap_or_default()
+                .saturating_add(self.env().transferred_value());
+            if amount < auction.reserve_price || amount <= auction.highest_bid {
+                return Err(DNSError::BidTooLow);
+            }
 
-            let name_count = self.owner_name_count.get(caller).unwrap_or_default();
-            self.owner_name_count.insert(caller, &(name_count + 1));
+            auction.highest_bidder = Some(caller);
+            auction.highest_bid = amount;
+            self.auctions.insert(auction_id, &auction);
+            self.auction_deposits.insert((auction_id, caller), &amount);
 
-            self.env().emit_event(NewNameClaimed { address: caller });
+            self.env().emit_event(BidPlaced {
+                auction_id,
+                bidder: caller,
+                amount,
+            });
             Ok(())
         }
 
-        #[ink(message)]
-        pub fn set_new_owner(
+        // place a sealed bid `make_bid_commitment(auction_id, amount, secret, caller)`,
+        // the transferred deposit must cover the amount revealed later
+        #[ink(message, payable)]
+        pub fn sealed_bid(
             &mut self,
-            name_id: i32,
-            new_owner: AccountId,
+            auction_id: AuctionId,
+            commitment: Hash,
         ) -> Result<(), DNSError> {
-            let name = self.domain_name.get(name_id);
+            self.get_running_auction(auction_id, AuctionKind::Sealed)?;
             let caller = self.env().caller();
 
-            match name {
-                Some(value) => {
-                    if value.default_address != caller {
-                        return Err(DNSError::NotAOwner);
-                    }
-                    // make sure domain_name.owner != new_owner
-                    if value.default_address == new_owner {
-                        return Err(DNSError::SameOwner);
-                    }
+            let deposit = self
+                .auction_deposits
+                .get((auction_id, caller))
+                .unwrap_or_default()
+                .saturating_add(self.env().transferred_value());
+
+            self.auction_deposits.insert((auction_id, caller), &deposit);
+            self.sealed_bids.insert((auction_id, caller), &commitment);
+            Ok(())
+        }
 
-                    // owner transfer so owner_name_count descrease
-                    let name_count = self.owner_name_count.get(caller).unwrap_or_default();
-                    self.owner_name_count.insert(caller, &(name_count - 1));
+        // reveal a sealed bid after the auction end time
+        #[ink(message)]
+        pub fn reveal_bid(
+            &mut self,
+            auction_id: AuctionId,
+            amount: Balance,
+            secret: Hash,
+        ) -> Result<(), DNSError> {
+            let mut auction = self
+                .auctions
+                .get(auction_id)
+                .ok_or(DNSError::AuctionNotFound)?;
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
 
-                    // remove domain name claimed of this id
-                    let name_claimed = self.claimed.get(name_id).unwrap_or_default();
-                    self.claimed.insert(name_id, &!name_claimed);
+            if auction.kind != AuctionKind::Sealed {
+                return Err(DNSError::WrongAuctionKind);
+            }
+            if now < auction.end_time {
+                return Err(DNSError::AuctionNotEnded);
+            }
+            if now >= auction.end_time.saturating_add(REVEAL_PERIOD) {
+                return Err(DNSError::AuctionEnded);
+            }
 
-                    let domain_name = DomainName {
-                        name: value.name,
-                        offer_state: value.offer_state,
-                        offer_price: value.offer_price,
-                        default_address: new_owner,
-                    };
+            let commitment = self.make_bid_commitment(auction_id, amount, secret, caller);
+            if self.sealed_bids.get((auction_id, caller)) != Some(commitment) {
+                return Err(DNSError::InvalidBidReveal);
+            }
+            let deposit = self
+                .auction_deposits
+                .get((auction_id, caller))
+                .unwrap_or_default();
+            if amount > deposit {
+                return Err(DNSError::InsufficientPayment);
+            }
 
-                    self.domain_name.insert(name_id, &domain_name);
-                }
-                None => (),
+            self.sealed_bids.remove((auction_id, caller));
+            if amount >= auction.reserve_price && amount > auction.highest_bid {
+                auction.highest_bidder = Some(caller);
+                auction.highest_bid = amount;
+                self.auctions.insert(auction_id, &auction);
             }
 
-            self.env().emit_event(SetNewOwner { address: new_owner });
+            self.env().emit_event(BidPlaced {
+                auction_id,
+                bidder: caller,
+                amount,
+            });
             Ok(())
         }
 
+        // refund caller's escrowed deposit, the leading bid stays locked until settlement
         #[ink(message)]
-        pub fn get_owner_domain_name(&self) -> Vec<DomainName> {
-            let mut domain_name: Vec<DomainName> = Vec::new();
+        pub fn withdraw_bid(&mut self, auction_id: AuctionId) -> Result<(), DNSError> {
+            let auction = self
+                .auctions
+                .get(auction_id)
+                .ok_or(DNSError::AuctionNotFound)?;
             let caller = self.env().caller();
 
-            for _item in 0..self.domain_name_id {
-                let name = self.domain_name.get(_item);
-                match name {
-                    Some(value) => {
-                        if value.default_address == caller {
-                            domain_name.push(value);
-                        }
-                    }
-                    None => (),
-                }
+            if !auction.settled && auction.highest_bidder == Some(caller) {
+                return Err(DNSError::HighestBidderCannotWithdraw);
+            }
+            // sealed deposits stay until bidding is over so they can't be used to probe bids
+            if auction.kind == AuctionKind::Sealed
+                && self.env().block_timestamp() < auction.end_time
+            {
+                return Err(DNSError::AuctionNotEnded);
             }
 
-            domain_name
+            let deposit = self
+                .auction_deposits
+                .take((auction_id, caller))
+                .ok_or(DNSError::NothingToWithdraw)?;
+            self.env()
+                .transfer(caller, deposit)
+                .map_err(|_| DNSError::TransferFailed)?;
+            Ok(())
         }
 
-        // get a owner of contract
+        // register the name to the highest bidder once bidding is over, the winning bid
+        // is paid into the treasury
         #[ink(message)]
-        pub fn get_owner(&self) -> AccountId {
-            self.owner.clone()
+        pub fn settle_auction(&mut self, auction_id: AuctionId) -> Result<(), DNSError> {
+            let mut auction = self
+                .auctions
+                .get(auction_id)
+                .ok_or(DNSError::AuctionNotFound)?;
+
+            if auction.settled {
+                return Err(DNSError::AuctionSettled);
+            }
+            let bidding_end = match auction.kind {
+                AuctionKind::Open => auction.end_time,
+                AuctionKind::Sealed => auction.end_time.saturating_add(REVEAL_PERIOD),
+            };
+            if self.env().block_timestamp() < bidding_end {
+                return Err(DNSError::AuctionNotEnded);
+            }
+
+            auction.settled = true;
+            self.auctions.insert(auction_id, &auction);
+            self.name_to_auction.remove(&auction.name);
+
+            if let Some(winner) = auction.highest_bidder {
+                let deposit = self
+                    .auction_deposits
+                    .get((auction_id, winner))
+                    .unwrap_or_default();
+                self.auction_deposits
+                    .insert((auction_id, winner), &(deposit - auction.highest_bid));
+                self.treasury = self.treasury.saturating_add(auction.highest_bid);
+
+                self.register_domain(auction.name.clone(), winner, YEAR, State::NotOffering, 0)?;
+            }
+
+            self.env().emit_event(AuctionSettled {
+                auction_id,
+                winner: auction.highest_bidder,
+                amount: auction.highest_bid,
+            });
+            Ok(())
         }
 
         #[ink(message)]
-        pub fn get_no_of_name_claimed(&self) -> i32 {
-            self.no_of_claimed_names.clone()
+        pub fn make_bid_commitment(
+            &self,
+            auction_id: AuctionId,
+            amount: Balance,
+            secret: Hash,
+            bidder: AccountId,
+        ) -> Hash {
+            let mut output = [0u8; 32];
+            ink::env::hash_encoded::<Blake2x256, _>(
+                &(auction_id, amount, secret, bidder),
+                &mut output,
+            );
+            output.into()
         }
 
-        // get domain name count
         #[ink(message)]
-        pub fn get_owner_name_count(&self, account_id: AccountId) -> i32 {
-            self.owner_name_count.get(account_id).unwrap_or_default() as i32
+        pub fn get_auction(&self, auction_id: AuctionId) -> Option<Auction> {
+            self.auctions.get(auction_id)
         }
 
         #[ink(message)]
-        pub fn is_claimed(&self, id: DomainNameId) -> bool {
-            self.claimed.get(id).unwrap_or_default()
+        pub fn get_auction_by_name(&self, name: String) -> Option<AuctionId> {
+            self.name_to_auction.get(normalize_name(&name).ok()?)
+        }
+
+        #[ink(message)]
+        pub fn get_auction_deposit(&self, auction_id: AuctionId, bidder: AccountId) -> Balance {
+            self.auction_deposits
+                .get((auction_id, bidder))
+                .unwrap_or_default()
+        }
+
+        // register a name for as many years as the transferred value pays for,
+        // only reachable through `reveal` so pending registrations can't be front-run
+        fn create_new_dns(
+            &mut self,
+            name: String,
+            offer_state: State,
+            offer_price: u128,
+        ) -> Result<(), DNSError> {
+            let name = normalize_top_level_name(&name)?;
+            let caller = self.env().caller();
+
+            if self.name_to_auction.contains(&name) {
+                return Err(DNSError::NameInAuction);
+            }
+
+            let value = self.env().transferred_value();
+            let period = self.registration_period(value, self.registration_price(&name))?;
+            self.register_domain(name, caller, period, offer_state, offer_price)?;
+
+            self.treasury = self.treasury.saturating_add(value);
+            Ok(())
+        }
+
+        // register a normalized top level name to `owner` for `period`
+        fn register_domain(
+            &mut self,
+            name: String,
+            owner: AccountId,
+            period: Timestamp,
+            offer_state: State,
+            offer_price: u128,
+        ) -> Result<DomainNameId, DNSError> {
+            // a private offer needs a buyer, which only `set_offer` takes
+            if offer_state == State::PrivateOffering {
+                return Err(DNSError::MissingAllowedBuyer);
+            }
+
+            // a registered name is claimable again once its grace period is over
+            let name_id = match self.name_to_id.get(&name) {
+                Some(name_id) => {
+                    if !self.is_available(&name) {
+                        return Err(DNSError::DomainAlreadyOwned);
+                    }
+                    let domain = self.domain_name.get(name_id).unwrap_or_default();
+                    self.release_domain(name_id, domain);
+                    name_id
+                }
+                None => self.next_domain_name_id(),
+            };
+
+            // check name mustn't be already claimed
+            if self.claimed.get(name_id).unwrap_or_default() {
+                return Err(DNSError::NameAlreadyClaimed);
+            }
+
+            // insert name to owner
+            self.name_to_owner.insert(&name, &owner);
+            self.name_to_id.insert(&name, &name_id);
+
+            let domain_name = DomainName {
+                name,
+                offer_state,
+                offer_price,
+                default_address: owner,
+                expires_at: self.env().block_timestamp().saturating_add(period),
+                allowed_buyer: None,
+                parent: None,
+                locked: false,
+            };
+
+            self.domain_name.insert(name_id, &domain_name);
+            self.claimed.insert(name_id, &true);
+            self.no_of_claimed_names += 1;
+
+            self.add_owned_domain(owner, name_id);
+
+            self.env().emit_event(NewNameClaimed {
+                name_id,
+                name: domain_name.name.clone(),
+                owner,
+                expires_at: domain_name.expires_at,
+            });
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(owner),
+                id: name_id.into(),
+            });
+            if domain_name.offer_state != State::NotOffering {
+                self.env().emit_event(OfferChanged {
+                    name_id,
+                    name: domain_name.name,
+                    offer_state: domain_name.offer_state,
+                    offer_price: domain_name.offer_price,
+                    allowed_buyer: None,
+                });
+            }
+            Ok(name_id)
+        }
+
+        #[ink(message)]
+        pub fn set_new_owner(
+            &mut self,
+            name_id: i32,
+            new_owner: AccountId,
+        ) -> Result<(), DNSError> {
+            let domain = self
+                .domain_name
+                .get(name_id)
+                .ok_or(DNSError::DomainNotFound)?;
+            let caller = self.env().caller();
+
+            if domain.default_address != caller {
+                return Err(DNSError::NotAOwner);
+            }
+            if !self.is_active(&domain) {
+                return Err(DNSError::DomainExpired);
+            }
+            if domain.locked {
+                return Err(DNSError::SubdomainLocked);
+            }
+            // make sure domain_name.owner != new_owner
+            if domain.default_address == new_owner {
+                return Err(DNSError::SameOwner);
+            }
+
+            self.transfer_domain(name_id, domain, new_owner);
+            Ok(())
+        }
+
+        // put a domain on offer, a private offer can only be bought by `allowed_buyer`
+        #[ink(message)]
+        pub fn set_offer(
+            &mut self,
+            name_id: DomainNameId,
+            offer_state: State,
+            offer_price: u128,
+            allowed_buyer: Option<AccountId>,
+        ) -> Result<(), DNSError> {
+            let mut domain = self
+                .domain_name
+                .get(name_id)
+                .ok_or(DNSError::DomainNotFound)?;
+
+            if domain.default_address != self.env().caller() {
+                return Err(DNSError::NotAOwner);
+            }
+            if !self.is_active(&domain) {
+                return Err(DNSError::DomainExpired);
+            }
+            if domain.locked {
+                return Err(DNSError::SubdomainLocked);
+            }
+
+            domain.allowed_buyer = match offer_state {
+                State::PrivateOffering => Some(allowed_buyer.ok_or(DNSError::MissingAllowedBuyer)?),
+                _ => None,
+            };
+            domain.offer_state = offer_state;
+            domain.offer_price = offer_price;
+
+            self.domain_name.insert(name_id, &domain);
+
+            self.env().emit_event(OfferChanged {
+                name_id,
+                name: domain.name,
+                offer_state: domain.offer_state,
+                offer_price,
+                allowed_buyer: domain.allowed_buyer,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancel_offer(&mut self, name_id: DomainNameId) -> Result<(), DNSError> {
+            self.set_offer(name_id, State::NotOffering, 0, None)
+        }
+
+        // buy an offered domain, the paid value is forwarded to the seller
+        #[ink(message, payable)]
+        pub fn buy_domain(&mut self, name_id: DomainNameId) -> Result<(), DNSError> {
+            let domain = self
+                .domain_name
+                .get(name_id)
+                .ok_or(DNSError::DomainNotFound)?;
+            let caller = self.env().caller();
+            let value = self.env().transferred_value();
+
+            if !self.is_active(&domain) {
+                return Err(DNSError::DomainExpired);
+            }
+            if domain.locked {
+                return Err(DNSError::SubdomainLocked);
+            }
+            match domain.offer_state {
+                State::NotOffering => return Err(DNSError::NotForSale),
+                State::PrivateOffering if domain.allowed_buyer != Some(caller) => {
+                    return Err(DNSError::NotAllowedBuyer)
+                }
+                _ => (),
+            }
+            if domain.default_address == caller {
+                return Err(DNSError::SameOwner);
+            }
+            if value < domain.offer_price {
+                return Err(DNSError::InsufficientPayment);
+            }
+
+            let seller = domain.default_address;
+            let name = domain.name.clone();
+            // the buyer gets the name without the seller's resolver records
+            self.clear_records(name_id);
+            self.transfer_domain(name_id, domain, caller);
+
+            // the treasury keeps its share of the sale
+            let fee = value.saturating_mul(Balance::from(self.fee_schedule.sale_fee_bps)) / 10_000;
+            self.treasury = self.treasury.saturating_add(fee);
+
+            self.env()
+                .transfer(seller, value - fee)
+                .map_err(|_| DNSError::TransferFailed)?;
+
+            self.env().emit_event(DomainSold {
+                name_id,
+                name,
+                seller,
+                buyer: caller,
+                price: value,
+            });
+            Ok(())
+        }
+
+        // first page of the caller's active domains, use `domains_of` for the rest
+        #[ink(message)]
+        pub fn get_owner_domain_name(&self) -> Vec<DomainName> {
+            let caller = self.env().caller();
+            self.domains_of(caller, 0, self.get_owner_name_count(caller).unsigned_abs())
+        }
+
+        // page through the domains of an account using the owner index, at most
+        // `MAX_PAGE_SIZE` entries are read. Expired domains are left out of the page after
+        // it is read, so a page can be shorter than `limit` while later pages still have
+        // entries
+        #[ink(message)]
+        pub fn domains_of(
+            &self,
+            account_id: AccountId,
+            offset: u32,
+            limit: u32,
+        ) -> Vec<DomainName> {
+            let count = self.get_owner_name_count(account_id).unsigned_abs();
+            let end = offset.saturating_add(limit.min(MAX_PAGE_SIZE)).min(count);
+            let mut domain_name: Vec<DomainName> = Vec::new();
+
+            for index in offset..end {
+                let domain = i32::try_from(index)
+                    .ok()
+                    .and_then(|index| self.owned_domains.get((account_id, index)))
+                    .and_then(|name_id| self.domain_name.get(name_id));
+                if let Some(value) = domain.filter(|value| self.is_active(value)) {
+                    domain_name.push(value);
+                }
+            }
+
+            domain_name
+        }
+
+        #[ink(message)]
+        pub fn get_domain(&self, name_id: DomainNameId) -> Option<DomainName> {
+            self.domain_name.get(name_id)
+        }
+
+        #[ink(message)]
+        pub fn get_domain_by_name(&self, name: String) -> Option<DomainName> {
+            let name_id = self.lookup(&normalize_name(&name).ok()?)?;
+            self.domain_name.get(name_id)
+        }
+
+        // create `label` under a domain of the caller, owned by `owner`
+        #[ink(message)]
+        pub fn create_subdomain(
+            &mut self,
+            parent_id: DomainNameId,
+            label: String,
+            owner: AccountId,
+        ) -> Result<DomainNameId, DNSError> {
+            let parent = self.ensure_owner_or_operator(parent_id)?;
+            let label = normalize_label(&label)?;
+
+            if self.subdomains.contains((parent_id, label.clone())) {
+                return Err(DNSError::DomainAlreadyOwned);
+            }
+
+            let name = label.clone() + "." + &parent.name;
+            if name.len() > MAX_NAME_LENGTH {
+                return Err(DNSError::NameTooLong);
+            }
+            let name_id = self.next_domain_name_id();
+
+            self.name_to_owner.insert(&name, &owner);
+            self.name_to_id.insert(&name, &name_id);
+
+            let domain_name = DomainName {
+                name,
+                offer_state: State::NotOffering,
+                offer_price: 0,
+                default_address: owner,
+                expires_at: Default::default(),
+                allowed_buyer: None,
+                parent: Some(parent_id),
+                locked: false,
+            };
+
+            self.domain_name.insert(name_id, &domain_name);
+            self.claimed.insert(name_id, &true);
+            self.no_of_claimed_names += 1;
+
+            let mut labels = self.subdomain_labels.get(parent_id).unwrap_or_default();
+            labels.push(label.clone());
+            self.subdomain_labels.insert(parent_id, &labels);
+            self.subdomains.insert((parent_id, label), &name_id);
+
+            self.add_owned_domain(owner, name_id);
+
+            self.env().emit_event(NewNameClaimed {
+                name_id,
+                name: domain_name.name,
+                owner,
+                expires_at: self.expiry_of(&parent),
+            });
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(owner),
+                id: name_id.into(),
+            });
+            Ok(name_id)
+        }
+
+        // a locked subdomain can't be transferred, offered or edited by its owner
+        #[ink(message)]
+        pub fn set_subdomain_lock(
+            &mut self,
+            name_id: DomainNameId,
+            locked: bool,
+        ) -> Result<(), DNSError> {
+            let mut domain = self.ensure_parent_owner_or_operator(name_id)?;
+            domain.locked = locked;
+            self.domain_name.insert(name_id, &domain);
+            Ok(())
+        }
+
+        // take a subdomain back to the owner of its parent domain
+        #[ink(message)]
+        pub fn reclaim_subdomain(&mut self, name_id: DomainNameId) -> Result<(), DNSError> {
+            let domain = self.ensure_parent_owner_or_operator(name_id)?;
+            let parent_owner = self
+                .domain_name
+                .get(domain.parent.unwrap_or_default())
+                .ok_or(DNSError::DomainNotFound)?
+                .default_address;
+
+            if domain.default_address == parent_owner {
+                return Err(DNSError::SameOwner);
+            }
+
+            // records set by the evicted owner don't stay with the name
+            self.clear_records(name_id);
+            self.transfer_domain(name_id, domain, parent_owner);
+            Ok(())
+        }
+
+        // extend a registration by as many years as the transferred value pays for,
+        // during the grace period only the previous owner can renew
+        #[ink(message, payable)]
+        pub fn renew(&mut self, name_id: DomainNameId) -> Result<(), DNSError> {
+            let mut domain = self
+                .domain_name
+                .get(name_id)
+                .ok_or(DNSError::DomainNotFound)?;
+            let caller = self.env().caller();
+            let now = self.env().block_timestamp();
+
+            // subdomains live as long as their top level domain
+            if domain.parent.is_some() {
+                return Err(DNSError::SubdomainNotRenewable);
+            }
+            if now >= domain.expires_at.saturating_add(GRACE_PERIOD) {
+                return Err(DNSError::DomainExpired);
+            }
+            if now >= domain.expires_at && domain.default_address != caller {
+                return Err(DNSError::NotAOwner);
+            }
+
+            let value = self.env().transferred_value();
+            let period = self.registration_period(value, self.fee_schedule.renewal_price)?;
+            domain.expires_at = domain.expires_at.saturating_add(period);
+            self.domain_name.insert(name_id, &domain);
+
+            self.treasury = self.treasury.saturating_add(value);
+
+            self.env().emit_event(Renewed {
+                name_id,
+                name: domain.name,
+                expires_at: domain.expires_at,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_expiry(&self, name_id: DomainNameId) -> Option<Timestamp> {
+            self.domain_name
+                .get(name_id)
+                .map(|domain| self.expiry_of(&domain))
+        }
+
+        // update registration prices, only by the contract owner
+        #[ink(message)]
+        pub fn set_fee_schedule(
+            &mut self,
+            length_prices: Vec<Balance>,
+            renewal_price: Balance,
+            sale_fee_bps: u16,
+        ) -> Result<(), DNSError> {
+            if self.env().caller() != self.owner {
+                return Err(DNSError::CallerIsNotOwner);
+            }
+            if length_prices.is_empty() || sale_fee_bps > 10_000 {
+                return Err(DNSError::InvalidFeeSchedule);
+            }
+
+            self.fee_schedule = FeeSchedule {
+                length_prices: length_prices.clone(),
+                renewal_price,
+                sale_fee_bps,
+            };
+
+            self.env().emit_event(FeeScheduleChanged {
+                length_prices,
+                renewal_price,
+                sale_fee_bps,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_fee_schedule(&self) -> FeeSchedule {
+            self.fee_schedule.clone()
+        }
+
+        // yearly registration price of a name
+        #[ink(message)]
+        pub fn get_registration_price(&self, name: String) -> Result<Balance, DNSError> {
+            Ok(self.registration_price(&normalize_top_level_name(&name)?))
+        }
+
+        // send collected fees out of the treasury, only by the contract owner
+        #[ink(message)]
+        pub fn withdraw(&mut self, to: AccountId, amount: Balance) -> Result<(), DNSError> {
+            if self.env().caller() != self.owner {
+                return Err(DNSError::CallerIsNotOwner);
+            }
+            if amount > self.treasury {
+                return Err(DNSError::InsufficientTreasury);
+            }
+
+            self.treasury -= amount;
+            self.env()
+                .transfer(to, amount)
+                .map_err(|_| DNSError::TransferFailed)?;
+
+            self.env().emit_event(Withdrawn { to, amount });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_treasury(&self) -> Balance {
+            self.treasury
+        }
+
+        // approve or revoke an operator managing and transferring all caller's domains
+        #[ink(message)]
+        pub fn set_operator(&mut self, operator: AccountId, approved: bool) {
+            let caller = self.env().caller();
+            if approved {
+                self.operators.insert((caller, operator), &true);
+            } else {
+                self.operators.remove((caller, operator));
+            }
+
+            self.env().emit_event(Approval {
+                owner: caller,
+                operator,
+                id: None,
+                approved,
+            });
+        }
+
+        #[ink(message)]
+        pub fn is_operator(&self, owner: AccountId, operator: AccountId) -> bool {
+            self.operators.get((owner, operator)).unwrap_or_default()
+        }
+
+        // point the domain name to a target account
+        #[ink(message)]
+        pub fn set_address(
+            &mut self,
+            name_id: DomainNameId,
+            target: AccountId,
+        ) -> Result<(), DNSError> {
+            let domain = self.ensure_owner_or_operator(name_id)?;
+            self.address_record.insert(name_id, &target);
+
+            self.env().emit_event(RecordUpdated {
+                name_id,
+                name: domain.name,
+                record: Record::Address(target),
+            });
+            Ok(())
+        }
+
+        // set a text record such as `url`, `avatar` or `email`, an empty value removes it
+        #[ink(message)]
+        pub fn set_text(
+            &mut self,
+            name_id: DomainNameId,
+            key: String,
+            value: String,
+        ) -> Result<(), DNSError> {
+            let domain = self.ensure_owner_or_operator(name_id)?;
+
+            // keep track of the keys so records can be cleared on expiry
+            let mut keys = self.text_keys.get(name_id).unwrap_or_default();
+            if value.is_empty() {
+                keys.retain(|k| k != &key);
+                self.text_record.remove((name_id, key.clone()));
+            } else {
+                if !keys.contains(&key) {
+                    keys.push(key.clone());
+                }
+                self.text_record.insert((name_id, key.clone()), &value);
+            }
+            self.text_keys.insert(name_id, &keys);
+
+            self.env().emit_event(RecordUpdated {
+                name_id,
+                name: domain.name,
+                record: Record::Text(key, value),
+            });
+            Ok(())
+        }
+
+        // set the content hash record, an empty hash removes it
+        #[ink(message)]
+        pub fn set_content_hash(
+            &mut self,
+            name_id: DomainNameId,
+            hash: Vec<u8>,
+        ) -> Result<(), DNSError> {
+            let domain = self.ensure_owner_or_operator(name_id)?;
+            if hash.is_empty() {
+                self.content_hash_record.remove(name_id);
+            } else {
+                self.content_hash_record.insert(name_id, &hash);
+            }
+
+            self.env().emit_event(RecordUpdated {
+                name_id,
+                name: domain.name,
+                record: Record::ContentHash(hash),
+            });
+            Ok(())
+        }
+
+        // resolve a name to its target account, falling back to the owner when no address is set
+        #[ink(message)]
+        pub fn resolve(&self, name: String) -> Option<AccountId> {
+            let name_id = self.lookup(&normalize_name(&name).ok()?)?;
+            let domain = self.domain_name.get(name_id)?;
+            if !self.is_active(&domain) {
+                return None;
+            }
+            self.address_record
+                .get(name_id)
+                .or(Some(domain.default_address))
+        }
+
+        // normalized form of a name, or the reason it would be rejected
+        #[ink(message)]
+        pub fn normalize(&self, name: String) -> Result<String, DNSError> {
+            normalize_name(&name)
+        }
+
+        // claim one of caller's own domains as its primary name
+        #[ink(message)]
+        pub fn set_primary_name(&mut self, name_id: DomainNameId) -> Result<(), DNSError> {
+            let domain = self
+                .domain_name
+                .get(name_id)
+                .ok_or(DNSError::DomainNotFound)?;
+            let caller = self.env().caller();
+
+            if domain.default_address != caller {
+                return Err(DNSError::NotAOwner);
+            }
+            if !self.is_active(&domain) {
+                return Err(DNSError::DomainExpired);
+            }
+
+            self.primary_name.insert(caller, &name_id);
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn clear_primary_name(&mut self) {
+            self.primary_name.remove(self.env().caller());
+        }
+
+        // get the primary name of an account
+        #[ink(message)]
+        pub fn reverse_lookup(&self, account_id: AccountId) -> Option<String> {
+            let name_id = self.primary_name.get(account_id)?;
+            self.domain_name
+                .get(name_id)
+                .filter(|domain| domain.default_address == account_id && self.is_active(domain))
+                .map(|domain| domain.name)
+        }
+
+        #[ink(message)]
+        pub fn get_text(&self, name_id: DomainNameId, key: String) -> Option<String> {
+            self.text_record.get((name_id, key))
+        }
+
+        #[ink(message)]
+        pub fn get_content_hash(&self, name_id: DomainNameId) -> Option<Vec<u8>> {
+            self.content_hash_record.get(name_id)
+        }
+
+        // get a owner of contract
+        #[ink(message)]
+        pub fn get_owner(&self) -> AccountId {
+            self.owner
+        }
+
+        #[ink(message)]
+        pub fn get_no_of_name_claimed(&self) -> i32 {
+            self.no_of_claimed_names
+        }
+
+        // get domain name count
+        #[ink(message)]
+        pub fn get_owner_name_count(&self, account_id: AccountId) -> i32 {
+            self.owner_name_count.get(account_id).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn is_claimed(&self, id: DomainNameId) -> bool {
+            self.claimed.get(id).unwrap_or_default()
+                && self
+                    .domain_name
+                    .get(id)
+                    .is_some_and(|domain| self.is_active(&domain))
+        }
+
+        // caller must be the domain owner or one of its approved operators
+        fn ensure_owner_or_operator(&self, name_id: DomainNameId) -> Result<DomainName, DNSError> {
+            let domain = self
+                .domain_name
+                .get(name_id)
+                .ok_or(DNSError::DomainNotFound)?;
+            let caller = self.env().caller();
+
+            if domain.default_address != caller && !self.is_operator(domain.default_address, caller)
+            {
+                return Err(DNSError::NotOwnerOrOperator);
+            }
+            if !self.is_active(&domain) {
+                return Err(DNSError::DomainExpired);
+            }
+            if domain.locked {
+                return Err(DNSError::SubdomainLocked);
+            }
+            Ok(domain)
+        }
+
+        // caller must be the owner or an operator of the subdomain's parent
+        fn ensure_parent_owner_or_operator(
+            &self,
+            name_id: DomainNameId,
+        ) -> Result<DomainName, DNSError> {
+            let domain = self
+                .domain_name
+                .get(name_id)
+                .ok_or(DNSError::DomainNotFound)?;
+            let parent_id = domain.parent.ok_or(DNSError::NotASubdomain)?;
+
+            self.ensure_owner_or_operator(parent_id)?;
+            Ok(domain)
+        }
+
+        // find the registered top level name, then walk the remaining labels down the subdomains
+        fn lookup(&self, name: &str) -> Option<DomainNameId> {
+            let labels: Vec<&str> = name.split('.').collect();
+
+            for split in (0..labels.len()).rev() {
+                if let Some(mut name_id) = self.name_to_id.get(labels[split..].join(".")) {
+                    for label in labels[..split].iter().rev() {
+                        name_id = self.subdomains.get((name_id, String::from(*label)))?;
+                    }
+                    return Some(name_id);
+                }
+            }
+            None
+        }
+
+        // subdomains expire together with their top level domain
+        fn expiry_of(&self, domain: &DomainName) -> Timestamp {
+            match domain
+                .parent
+                .and_then(|parent_id| self.domain_name.get(parent_id))
+            {
+                Some(parent) => self.expiry_of(&parent),
+                None => domain.expires_at,
+            }
+        }
+
+        // a name is available when it was never registered or its grace period is over
+        fn is_available(&self, name: &str) -> bool {
+            match self
+                .name_to_id
+                .get(name)
+                .and_then(|id| self.domain_name.get(id))
+            {
+                Some(domain) => {
+                    self.env().block_timestamp()
+                        >= self.expiry_of(&domain).saturating_add(GRACE_PERIOD)
+                }
+                None => true,
+            }
+        }
+
+        // auction must be of `kind` and still accepting bids
+        fn get_running_auction(
+            &self,
+            auction_id: AuctionId,
+            kind: AuctionKind,
+        ) -> Result<Auction, DNSError> {
+            let auction = self
+                .auctions
+                .get(auction_id)
+                .ok_or(DNSError::AuctionNotFound)?;
+
+            if auction.kind != kind {
+                return Err(DNSError::WrongAuctionKind);
+            }
+            if self.env().block_timestamp() >= auction.end_time {
+                return Err(DNSError::AuctionEnded);
+            }
+            Ok(auction)
+        }
+
+        // registration is valid until its expiry timestamp
+        fn is_active(&self, domain: &DomainName) -> bool {
+            self.env().block_timestamp() < self.expiry_of(domain)
+        }
+
+        // owner of a domain while it is active
+        fn active_owner(&self, name_id: DomainNameId) -> Option<AccountId> {
+            self.domain_name
+                .get(name_id)
+                .filter(|domain| self.is_active(domain))
+                .map(|domain| domain.default_address)
+        }
+
+        // yearly price of a normalized top level name by the length of its label
+        fn registration_price(&self, name: &str) -> Balance {
+            let length = name.split('.').next().unwrap_or_default().len();
+            let prices = &self.fee_schedule.length_prices;
+
+            prices
+                .get(length.saturating_sub(1))
+                .or(prices.last())
+                .copied()
+                .unwrap_or_default()
+        }
+
+        // registration period paid for by `value`, at least one whole year must be paid and
+        // nothing more, a free name is registered for a year and takes no payment
+        fn registration_period(
+            &self,
+            value: Balance,
+            price_per_year: Balance,
+        ) -> Result<Timestamp, DNSError> {
+            if price_per_year == 0 {
+                if value != 0 {
+                    return Err(DNSError::InexactPayment);
+                }
+                return Ok(YEAR);
+            }
+
+            let years = value / price_per_year;
+            if years == 0 {
+                return Err(DNSError::InsufficientPayment);
+            }
+            if !value.is_multiple_of(price_per_year) {
+                return Err(DNSError::InexactPayment);
+            }
+            Ok(YEAR.saturating_mul(u64::try_from(years).unwrap_or(u64::MAX)))
+        }
+
+        // move a domain to a new owner, keeping owner counts and lookups in sync
+        fn transfer_domain(
+            &mut self,
+            name_id: DomainNameId,
+            mut domain: DomainName,
+            new_owner: AccountId,
+        ) {
+            let old_owner = domain.default_address;
+
+            // owner transfer so owner_name_count descrease
+            self.remove_owned_domain(old_owner, name_id);
+            self.add_owned_domain(new_owner, name_id);
+
+            // previous owner can no longer use this name as primary name
+            if self.primary_name.get(old_owner) == Some(name_id) {
+                self.primary_name.remove(old_owner);
+            }
+
+            // approvals of the previous owner don't carry over
+            self.token_approvals.remove(name_id);
+
+            // a transferred domain is no longer on offer
+            domain.offer_state = State::NotOffering;
+            domain.offer_price = 0;
+            domain.allowed_buyer = None;
+            domain.default_address = new_owner;
+
+            self.name_to_owner.insert(&domain.name, &new_owner);
+            self.domain_name.insert(name_id, &domain);
+
+            self.env().emit_event(SetNewOwner {
+                name_id,
+                name: domain.name,
+                old_owner,
+                new_owner,
+            });
+            self.env().emit_event(Transfer {
+                from: Some(old_owner),
+                to: Some(new_owner),
+                id: name_id.into(),
+            });
+        }
+
+        // append a domain to the owner index
+        fn add_owned_domain(&mut self, owner: AccountId, name_id: DomainNameId) {
+            let name_count = self.owner_name_count.get(owner).unwrap_or_default();
+            self.owned_domains.insert((owner, name_count), &name_id);
+            self.owned_index.insert(name_id, &name_count);
+            self.owner_name_count.insert(owner, &(name_count + 1));
+        }
+
+        // remove a domain from the owner index by moving the owner's last domain into its slot
+        fn remove_owned_domain(&mut self, owner: AccountId, name_id: DomainNameId) {
+            let name_count = self.owner_name_count.get(owner).unwrap_or_default();
+            let last = name_count - 1;
+            let index = self.owned_index.take(name_id).unwrap_or_default();
+
+            if let Some(last_id) = self.owned_domains.take((owner, last)) {
+                if index != last {
+                    self.owned_domains.insert((owner, index), &last_id);
+                    self.owned_index.insert(last_id, &index);
+                }
+            }
+            self.owner_name_count.insert(owner, &last);
+        }
+
+        // remove all resolver records of a domain
+        fn clear_records(&mut self, name_id: DomainNameId) {
+            self.address_record.remove(name_id);
+            self.content_hash_record.remove(name_id);
+            for key in self.text_keys.take(name_id).unwrap_or_default() {
+                self.text_record.remove((name_id, key));
+            }
+        }
+
+        // drop the previous owner's claim and records of an expired name
+        fn release_domain(&mut self, name_id: DomainNameId, domain: DomainName) {
+            let owner = domain.default_address;
+            self.remove_owned_domain(owner, name_id);
+
+            if self.primary_name.get(owner) == Some(name_id) {
+                self.primary_name.remove(owner);
+            }
+
+            self.token_approvals.remove(name_id);
+            self.clear_records(name_id);
+
+            self.claimed.insert(name_id, &false);
+            self.no_of_claimed_names -= 1;
+
+            self.env().emit_event(Expired {
+                name_id,
+                name: domain.name,
+                owner,
+            });
+
+            // subdomains are released together with their parent
+            for label in self.subdomain_labels.take(name_id).unwrap_or_default() {
+                let Some(sub_id) = self.subdomains.take((name_id, label)) else {
+                    continue;
+                };
+                if let Some(sub) = self.domain_name.take(sub_id) {
+                    self.name_to_owner.remove(&sub.name);
+                    self.name_to_id.remove(&sub.name);
+                    self.release_domain(sub_id, sub);
+                }
+            }
         }
 
         #[inline]
@@ -233,5 +1785,1357 @@ mod dns_contract {
             self.domain_name_id += 1;
             id
         }
+
+        #[inline]
+        fn next_auction_id(&mut self) -> AuctionId {
+            let id = self.auction_id;
+            self.auction_id += 1;
+            id
+        }
+    }
+
+    impl PSP34 for DnsContract {
+        // the contract address identifies the collection
+        #[ink(message)]
+        fn collection_id(&self) -> Id {
+            Id::Bytes(<_ as AsRef<[u8; 32]>>::as_ref(&self.env().account_id()).to_vec())
+        }
+
+        // expired domains stay in the owner index until released, so only active ones count
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u32 {
+            let count = self.get_owner_name_count(owner);
+            (0..count)
+                .filter_map(|index| self.owned_domains.get((owner, index)))
+                .filter(|name_id| self.active_owner(*name_id) == Some(owner))
+                .count() as u32
+        }
+
+        #[ink(message)]
+        fn owner_of(&self, id: Id) -> Option<AccountId> {
+            id.name_id().and_then(|name_id| self.active_owner(name_id))
+        }
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool {
+            self.is_operator(owner, operator)
+                || id.and_then(|id| id.name_id()).is_some_and(|name_id| {
+                    self.active_owner(name_id) == Some(owner)
+                        && self.token_approvals.get(name_id) == Some(operator)
+                })
+        }
+
+        // approve `operator` for a single domain, or for all caller's domains when `id` is `None`
+        #[ink(message)]
+        fn approve(
+            &mut self,
+            operator: AccountId,
+            id: Option<Id>,
+            approved: bool,
+        ) -> Result<(), PSP34Error> {
+            let caller = self.env().caller();
+            if operator == caller {
+                return Err(PSP34Error::SelfApprove);
+            }
+
+            let Some(id) = id else {
+                self.set_operator(operator, approved);
+                return Ok(());
+            };
+
+            let name_id = id.name_id().ok_or(PSP34Error::TokenNotExists)?;
+            let owner = self
+                .active_owner(name_id)
+                .ok_or(PSP34Error::TokenNotExists)?;
+            if owner != caller && !self.is_operator(owner, caller) {
+                return Err(PSP34Error::NotApproved);
+            }
+
+            if approved {
+                self.token_approvals.insert(name_id, &operator);
+            } else {
+                self.token_approvals.remove(name_id);
+            }
+
+            self.env().emit_event(Approval {
+                owner,
+                operator,
+                id: Some(name_id.into()),
+                approved,
+            });
+            Ok(())
+        }
+
+        // transfer a domain as its owner, an operator of the owner or the approved account
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, id: Id, _data: Vec<u8>) -> Result<(), PSP34Error> {
+            let name_id = id.name_id().ok_or(PSP34Error::TokenNotExists)?;
+            let domain = self
+                .domain_name
+                .get(name_id)
+                .filter(|domain| self.is_active(domain))
+                .ok_or(PSP34Error::TokenNotExists)?;
+            let caller = self.env().caller();
+            let owner = domain.default_address;
+
+            if !self.allowance(owner, caller, Some(id)) && owner != caller {
+                return Err(PSP34Error::NotApproved);
+            }
+            if domain.locked {
+                return Err(PSP34Error::Custom(String::from("SubdomainLocked")));
+            }
+            if owner == to {
+                return Err(PSP34Error::Custom(String::from("SameOwner")));
+            }
+
+            self.transfer_domain(name_id, domain, to);
+            Ok(())
+        }
+
+        // number of registered domains, expiry is only noticed when a name is released so
+        // an expired domain is counted until it is registered again after its grace period
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            self.no_of_claimed_names as Balance
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn default_accounts() -> ink::env::test::DefaultAccounts<ink::env::DefaultEnvironment> {
+            ink::env::test::default_accounts::<Environment>()
+        }
+
+        fn set_next_caller(caller: AccountId) {
+            ink::env::test::set_caller::<Environment>(caller);
+        }
+
+        fn set_balance(account_id: AccountId, balance: Balance) {
+            ink::env::test::set_account_balance::<ink::env::DefaultEnvironment>(account_id, balance)
+        }
+
+        fn get_balance(account_id: AccountId) -> Balance {
+            ink::env::test::get_account_balance::<ink::env::DefaultEnvironment>(account_id)
+                .expect("cannot get account balance")
+        }
+
+        fn set_block_timestamp(timestamp: Timestamp) {
+            ink::env::test::set_block_timestamp::<Environment>(timestamp);
+        }
+
+        fn create_contract() -> DnsContract {
+            set_next_caller(default_accounts().alice);
+            DnsContract::new(0)
+        }
+
+        fn create_name(contract: &mut DnsContract, caller: AccountId, name: &str) {
+            set_next_caller(caller);
+            contract
+                .create_new_dns(String::from(name), State::NotOffering, 0)
+                .expect("creating name failed");
+        }
+
+        #[ink::test]
+        fn new_works() {
+            let contract = create_contract();
+
+            assert_eq!(contract.get_owner(), default_accounts().alice);
+            assert_eq!(contract.get_no_of_name_claimed(), 0);
+            assert_eq!(contract.get_owner_name_count(default_accounts().alice), 0);
+            assert!(!contract.is_claimed(1));
+        }
+
+        #[ink::test]
+        fn create_new_dns_works() {
+            let mut contract = create_contract();
+            create_name(&mut contract, default_accounts().bob, "bob.dot");
+
+            assert!(contract.is_claimed(1));
+            assert_eq!(contract.get_no_of_name_claimed(), 1);
+            assert_eq!(contract.get_owner_name_count(default_accounts().bob), 1);
+            assert_eq!(
+                contract.resolve(String::from("bob.dot")),
+                Some(default_accounts().bob)
+            );
+            assert_eq!(contract.domain_name_id, 2);
+        }
+
+        #[ink::test]
+        fn create_new_dns_rejects_duplicate_name() {
+            let mut contract = create_contract();
+            create_name(&mut contract, default_accounts().bob, "bob.dot");
+
+            set_next_caller(default_accounts().charlie);
+            assert_eq!(
+                contract.create_new_dns(String::from("bob.dot"), State::NotOffering, 0),
+                Err(DNSError::DomainAlreadyOwned)
+            );
+            assert_eq!(
+                contract.create_new_dns(String::from("BOB.dot"), State::NotOffering, 0),
+                Err(DNSError::DomainAlreadyOwned)
+            );
+
+            // rejected names don't consume ids or change counters
+            assert_eq!(contract.domain_name_id, 2);
+            assert_eq!(contract.get_no_of_name_claimed(), 1);
+            assert_eq!(contract.get_owner_name_count(default_accounts().charlie), 0);
+        }
+
+        #[ink::test]
+        fn create_new_dns_validates_name() {
+            let mut contract = create_contract();
+
+            for (name, error) in [
+                ("", DNSError::EmptyName),
+                ("bob", DNSError::InvalidTld),
+                ("bob.com", DNSError::InvalidTld),
+                ("b ob.dot", DNSError::InvalidCharacter),
+                ("bоb.dot", DNSError::InvalidCharacter),
+                ("-bob.dot", DNSError::InvalidHyphen),
+                ("bo.dot", DNSError::NameTooShort),
+                ("api.bob.dot", DNSError::NotATopLevelName),
+                ("bob..dot", DNSError::InvalidLabel),
+            ] {
+                assert_eq!(
+                    contract.create_new_dns(String::from(name), State::NotOffering, 0),
+                    Err(error)
+                );
+            }
+            assert_eq!(contract.get_no_of_name_claimed(), 0);
+        }
+
+        #[ink::test]
+        fn create_new_dns_with_offer_works() {
+            type Event = <DnsContract as ::ink::reflect::ContractEventBase>::Type;
+
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+
+            // nobody could buy a private offer without an allowed buyer
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.create_new_dns(String::from("bob.dot"), State::PrivateOffering, 50),
+                Err(DNSError::MissingAllowedBuyer)
+            );
+            assert_eq!(
+                contract.create_new_dns(String::from("bob.dot"), State::PublicOffering, 50),
+                Ok(())
+            );
+
+            let event = ink::env::test::recorded_events()
+                .last()
+                .expect("no event was emitted");
+            let event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            assert!(matches!(
+                event,
+                Event::OfferChanged(OfferChanged {
+                    name_id: 1,
+                    offer_state: State::PublicOffering,
+                    offer_price: 50,
+                    allowed_buyer: None,
+                    ..
+                })
+            ));
+        }
+
+        #[ink::test]
+        fn commit_reveal_works() {
+            let mut contract = create_contract();
+            let secret = Hash::from([7u8; 32]);
+            let name = String::from("bob.dot");
+
+            set_next_caller(default_accounts().bob);
+            let commitment = contract.make_commitment(name.clone(), secret, default_accounts().bob);
+            assert_eq!(contract.commit(commitment), Ok(()));
+            assert_eq!(
+                contract.reveal(name.clone(), secret, State::NotOffering, 0),
+                Err(DNSError::CommitmentTooNew)
+            );
+
+            // only the committer can reveal
+            set_block_timestamp(MIN_COMMITMENT_AGE);
+            set_next_caller(default_accounts().charlie);
+            assert_eq!(
+                contract.reveal(name.clone(), secret, State::NotOffering, 0),
+                Err(DNSError::CommitmentNotFound)
+            );
+
+            set_next_caller(default_accounts().bob);
+            assert_eq!(
+                contract.reveal(name.clone(), secret, State::NotOffering, 0),
+                Ok(())
+            );
+            assert_eq!(contract.resolve(name), Some(default_accounts().bob));
+            assert_eq!(contract.get_commitment(commitment), None);
+        }
+
+        #[ink::test]
+        fn commitment_age_can_be_set() {
+            set_next_caller(default_accounts().alice);
+            let mut contract = DnsContract::with_commitment_age(0, 0);
+            let secret = Hash::from([7u8; 32]);
+            let name = String::from("bob.dot");
+
+            set_next_caller(default_accounts().bob);
+            let commitment = contract.make_commitment(name.clone(), secret, default_accounts().bob);
+            assert_eq!(contract.commit(commitment), Ok(()));
+            assert_eq!(
+                contract.reveal(name.clone(), secret, State::NotOffering, 0),
+                Ok(())
+            );
+            assert_eq!(contract.resolve(name), Some(default_accounts().bob));
+        }
+
+        #[ink::test]
+        fn stale_commitment_can_be_cleaned() {
+            let mut contract = create_contract();
+            let commitment = Hash::from([1u8; 32]);
+
+            assert_eq!(contract.commit(commitment), Ok(()));
+            assert_eq!(
+                contract.clean_commitment(commitment),
+                Err(DNSError::CommitmentNotStale)
+            );
+
+            set_block_timestamp(MAX_COMMITMENT_AGE + 1);
+            assert_eq!(contract.clean_commitment(commitment), Ok(()));
+            assert_eq!(contract.get_commitment(commitment), None);
+        }
+
+        #[ink::test]
+        fn set_new_owner_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.set_new_owner(1, accounts.charlie), Ok(()));
+
+            // the name stays claimed and counters move to the new owner
+            assert!(contract.is_claimed(1));
+            assert_eq!(contract.get_no_of_name_claimed(), 1);
+            assert_eq!(contract.get_owner_name_count(accounts.bob), 0);
+            assert_eq!(contract.get_owner_name_count(accounts.charlie), 1);
+            assert_eq!(
+                contract.name_to_owner.get(String::from("bob.dot")),
+                Some(accounts.charlie)
+            );
+            assert!(contract.get_owner_domain_name().is_empty());
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(contract.get_owner_domain_name().len(), 1);
+        }
+
+        #[ink::test]
+        fn set_new_owner_fails() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_new_owner(1, accounts.charlie),
+                Err(DNSError::NotAOwner)
+            );
+
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.set_new_owner(1, accounts.bob),
+                Err(DNSError::SameOwner)
+            );
+            assert_eq!(
+                contract.set_new_owner(2, accounts.charlie),
+                Err(DNSError::DomainNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn get_owner_domain_name_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+            create_name(&mut contract, accounts.charlie, "charlie.dot");
+            create_name(&mut contract, accounts.bob, "bobby.dot");
+
+            set_next_caller(accounts.bob);
+            let names: Vec<String> = contract
+                .get_owner_domain_name()
+                .into_iter()
+                .map(|domain| domain.name)
+                .collect();
+            assert_eq!(names, [String::from("bob.dot"), String::from("bobby.dot")]);
+
+            assert_eq!(contract.domains_of(accounts.bob, 1, 10).len(), 1);
+            assert_eq!(contract.domains_of(accounts.charlie, 0, 10).len(), 1);
+            assert_eq!(contract.get_no_of_name_claimed(), 3);
+
+            // a page never reads more than `MAX_PAGE_SIZE` entries
+            for index in 0..MAX_PAGE_SIZE {
+                create_name(&mut contract, accounts.bob, &format!("name{index}.dot"));
+            }
+            assert_eq!(
+                contract.domains_of(accounts.bob, 0, u32::MAX).len(),
+                MAX_PAGE_SIZE as usize
+            );
+            assert_eq!(
+                contract
+                    .domains_of(accounts.bob, MAX_PAGE_SIZE, u32::MAX)
+                    .len(),
+                2
+            );
+        }
+
+        #[ink::test]
+        fn expiry_and_renewal_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+
+            set_block_timestamp(YEAR);
+            assert!(!contract.is_claimed(1));
+            assert_eq!(contract.resolve(String::from("bob.dot")), None);
+
+            // only the previous owner can renew during the grace period
+            set_next_caller(accounts.charlie);
+            assert_eq!(contract.renew(1), Err(DNSError::NotAOwner));
+            assert_eq!(
+                contract.create_new_dns(String::from("bob.dot"), State::NotOffering, 0),
+                Err(DNSError::DomainAlreadyOwned)
+            );
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.renew(1), Ok(()));
+            assert!(contract.is_claimed(1));
+            assert_eq!(contract.get_expiry(1), Some(2 * YEAR));
+        }
+
+        #[ink::test]
+        fn expired_name_can_be_claimed_again() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+
+            set_block_timestamp(YEAR + GRACE_PERIOD);
+            create_name(&mut contract, accounts.charlie, "bob.dot");
+
+            assert_eq!(contract.get_owner_name_count(accounts.bob), 0);
+            assert_eq!(contract.get_owner_name_count(accounts.charlie), 1);
+            assert_eq!(contract.get_no_of_name_claimed(), 1);
+            assert_eq!(
+                contract.resolve(String::from("bob.dot")),
+                Some(accounts.charlie)
+            );
+        }
+
+        #[ink::test]
+        fn registration_requires_fee() {
+            set_next_caller(default_accounts().alice);
+            let mut contract = DnsContract::new(100);
+
+            set_next_caller(default_accounts().bob);
+            assert_eq!(
+                contract.create_new_dns(String::from("bob.dot"), State::NotOffering, 0),
+                Err(DNSError::InsufficientPayment)
+            );
+
+            ink::env::test::set_value_transferred::<Environment>(200);
+            assert_eq!(
+                contract.create_new_dns(String::from("bob.dot"), State::NotOffering, 0),
+                Ok(())
+            );
+            assert_eq!(contract.get_expiry(1), Some(2 * YEAR));
+            assert_eq!(contract.get_treasury(), 200);
+        }
+
+        #[ink::test]
+        fn records_and_reverse_lookup_work() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.set_address(1, accounts.django), Ok(()));
+            assert_eq!(
+                contract.set_text(1, String::from("url"), String::from("https://bob.dot")),
+                Ok(())
+            );
+            assert_eq!(contract.set_primary_name(1), Ok(()));
+
+            assert_eq!(
+                contract.resolve(String::from("Bob.dot")),
+                Some(accounts.django)
+            );
+            assert_eq!(
+                contract.get_text(1, String::from("url")),
+                Some(String::from("https://bob.dot"))
+            );
+            assert_eq!(
+                contract.reverse_lookup(accounts.bob),
+                Some(String::from("bob.dot"))
+            );
+
+            // records can't be set by others, reverse record is cleared on transfer
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_address(1, accounts.charlie),
+                Err(DNSError::NotOwnerOrOperator)
+            );
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.set_new_owner(1, accounts.charlie), Ok(()));
+            assert_eq!(contract.reverse_lookup(accounts.bob), None);
+        }
+
+        #[ink::test]
+        fn buy_domain_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.set_address(1, accounts.django), Ok(()));
+            assert_eq!(
+                contract.set_text(1, String::from("url"), String::from("https://bob.dot")),
+                Ok(())
+            );
+            assert_eq!(contract.set_content_hash(1, Vec::from([1u8; 32])), Ok(()));
+            assert_eq!(
+                contract.set_offer(1, State::PublicOffering, 50, None),
+                Ok(())
+            );
+
+            set_balance(accounts.bob, 0);
+            set_balance(accounts.charlie, 100);
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.buy_domain(1), 10),
+                Err(DNSError::InsufficientPayment)
+            );
+            assert_eq!(ink::env::pay_with_call!(contract.buy_domain(1), 50), Ok(()));
+
+            assert_eq!(get_balance(accounts.bob), 50);
+            assert_eq!(contract.owner_of(Id::U32(1)), Some(accounts.charlie));
+
+            // the seller's records don't carry over to the buyer
+            assert_eq!(
+                contract.resolve(String::from("bob.dot")),
+                Some(accounts.charlie)
+            );
+            assert_eq!(contract.get_text(1, String::from("url")), None);
+            assert_eq!(contract.get_content_hash(1), None);
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+            assert_eq!(contract.balance_of(accounts.charlie), 1);
+
+            // a sold domain is no longer offered
+            set_balance(accounts.django, 100);
+            set_next_caller(accounts.django);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.buy_domain(1), 50),
+                Err(DNSError::NotForSale)
+            );
+        }
+
+        #[ink::test]
+        fn subdomain_resolution_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "myproject.dot");
+
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.create_subdomain(1, String::from("api"), accounts.charlie),
+                Ok(2)
+            );
+            assert_eq!(
+                contract.resolve(String::from("api.myproject.dot")),
+                Some(accounts.charlie)
+            );
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(contract.set_address(2, accounts.eve), Ok(()));
+            assert_eq!(
+                contract.set_text(2, String::from("url"), String::from("https://api")),
+                Ok(())
+            );
+            assert_eq!(contract.set_content_hash(2, Vec::from([2u8; 32])), Ok(()));
+            assert_eq!(
+                contract.resolve(String::from("api.myproject.dot")),
+                Some(accounts.eve)
+            );
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.set_subdomain_lock(2, true), Ok(()));
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_new_owner(2, accounts.django),
+                Err(DNSError::SubdomainLocked)
+            );
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.reclaim_subdomain(2), Ok(()));
+            assert_eq!(contract.owner_of(Id::U32(2)), Some(accounts.bob));
+
+            // the evicted owner's records are gone, the name resolves to its new owner
+            assert_eq!(
+                contract.resolve(String::from("api.myproject.dot")),
+                Some(accounts.bob)
+            );
+            assert_eq!(contract.get_text(2, String::from("url")), None);
+            assert_eq!(contract.get_content_hash(2), None);
+        }
+
+        #[ink::test]
+        fn psp34_counts_only_active_domains() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+            create_name(&mut contract, accounts.bob, "bobby.dot");
+
+            assert_eq!(contract.balance_of(accounts.bob), 2);
+            assert_eq!(contract.total_supply(), 2);
+
+            // any integer id with the domain's value refers to it
+            assert_eq!(contract.owner_of(Id::U8(2)), Some(accounts.bob));
+            assert_eq!(contract.owner_of(Id::U128(2)), Some(accounts.bob));
+            assert_eq!(contract.owner_of(Id::U64(u64::MAX)), None);
+            assert_eq!(contract.owner_of(Id::Bytes(vec![1])), None);
+            assert!(matches!(contract.collection_id(), Id::Bytes(bytes) if bytes.len() == 32));
+
+            set_block_timestamp(YEAR);
+            assert_eq!(contract.owner_of(Id::U32(1)), None);
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+            assert_eq!(contract.total_supply(), 2);
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.renew(1), Ok(()));
+            assert_eq!(contract.balance_of(accounts.bob), 1);
+
+            // the expired name leaves the supply once it is registered again
+            set_block_timestamp(YEAR + GRACE_PERIOD);
+            create_name(&mut contract, accounts.charlie, "bobby.dot");
+            assert_eq!(contract.balance_of(accounts.charlie), 1);
+            assert_eq!(contract.total_supply(), 2);
+        }
+
+        #[ink::test]
+        fn psp34_approvals_work() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+            create_name(&mut contract, accounts.bob, "bobby.dot");
+
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.approve(accounts.bob, Some(Id::U32(1)), true),
+                Err(PSP34Error::SelfApprove)
+            );
+            assert_eq!(
+                contract.approve(accounts.charlie, Some(Id::U32(1)), true),
+                Ok(())
+            );
+            assert!(contract.allowance(accounts.bob, accounts.charlie, Some(Id::U32(1))));
+            assert!(!contract.allowance(accounts.bob, accounts.charlie, Some(Id::U32(2))));
+            assert!(!contract.allowance(accounts.bob, accounts.charlie, None));
+
+            set_next_caller(accounts.django);
+            assert_eq!(
+                contract.approve(accounts.django, Some(Id::U32(1)), true),
+                Err(PSP34Error::SelfApprove)
+            );
+            assert_eq!(
+                contract.approve(accounts.eve, Some(Id::U32(1)), true),
+                Err(PSP34Error::NotApproved)
+            );
+            assert_eq!(
+                contract.approve(accounts.eve, Some(Id::U32(3)), true),
+                Err(PSP34Error::TokenNotExists)
+            );
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.approve(accounts.django, None, true), Ok(()));
+            assert!(contract.allowance(accounts.bob, accounts.django, None));
+            assert!(contract.allowance(accounts.bob, accounts.django, Some(Id::U32(2))));
+
+            // an operator can approve single domains of the owner
+            set_next_caller(accounts.django);
+            assert_eq!(
+                contract.approve(accounts.eve, Some(Id::U32(2)), true),
+                Ok(())
+            );
+            assert!(contract.allowance(accounts.bob, accounts.eve, Some(Id::U32(2))));
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.approve(accounts.django, None, false), Ok(()));
+            assert!(!contract.allowance(accounts.bob, accounts.django, Some(Id::U32(2))));
+        }
+
+        #[ink::test]
+        fn psp34_transfer_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            create_name(&mut contract, accounts.bob, "bob.dot");
+            create_name(&mut contract, accounts.bob, "bobby.dot");
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.transfer(accounts.charlie, Id::U32(1), Vec::new()),
+                Err(PSP34Error::NotApproved)
+            );
+            assert_eq!(
+                contract.transfer(accounts.charlie, Id::U32(3), Vec::new()),
+                Err(PSP34Error::TokenNotExists)
+            );
+
+            // the account approved for a single domain can transfer it once
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.approve(accounts.charlie, Some(Id::U32(1)), true),
+                Ok(())
+            );
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.transfer(accounts.django, Id::U32(1), Vec::new()),
+                Ok(())
+            );
+            assert_eq!(contract.owner_of(Id::U32(1)), Some(accounts.django));
+            assert!(!contract.allowance(accounts.django, accounts.charlie, Some(Id::U32(1))));
+            assert_eq!(
+                contract.transfer(accounts.charlie, Id::U32(1), Vec::new()),
+                Err(PSP34Error::NotApproved)
+            );
+
+            // an operator can transfer any domain of the owner
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.approve(accounts.eve, None, true), Ok(()));
+            set_next_caller(accounts.eve);
+            assert_eq!(
+                contract.transfer(accounts.eve, Id::U32(2), Vec::new()),
+                Ok(())
+            );
+
+            assert_eq!(contract.balance_of(accounts.bob), 0);
+            assert_eq!(contract.balance_of(accounts.django), 1);
+            assert_eq!(contract.balance_of(accounts.eve), 1);
+            assert_eq!(contract.total_supply(), 2);
+        }
+
+        #[ink::test]
+        fn open_auction_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            let name = String::from("premium.dot");
+
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.start_auction(name.clone(), AuctionKind::Open, 100, 10),
+                Err(DNSError::CallerIsNotOwner)
+            );
+            set_next_caller(accounts.alice);
+            assert_eq!(
+                contract.start_auction(name.clone(), AuctionKind::Open, 100, 0),
+                Err(DNSError::InvalidEndTime)
+            );
+            assert_eq!(
+                contract.start_auction(name.clone(), AuctionKind::Open, 100, 10),
+                Ok(1)
+            );
+            assert_eq!(
+                contract.start_auction(name.clone(), AuctionKind::Open, 100, 10),
+                Err(DNSError::NameInAuction)
+            );
+
+            set_balance(accounts.bob, 1_000);
+            set_balance(accounts.charlie, 1_000);
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.sealed_bid(1, Hash::from([0u8; 32])), 0),
+                Err(DNSError::WrongAuctionKind)
+            );
+            assert_eq!(
+                ink::env::pay_with_call!(contract.bid(1), 50),
+                Err(DNSError::BidTooLow)
+            );
+            assert_eq!(ink::env::pay_with_call!(contract.bid(1), 100), Ok(()));
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.bid(1), 100),
+                Err(DNSError::BidTooLow)
+            );
+            assert_eq!(ink::env::pay_with_call!(contract.bid(1), 150), Ok(()));
+
+            // a bid raises the bidder's escrowed deposit
+            set_next_caller(accounts.bob);
+            assert_eq!(ink::env::pay_with_call!(contract.bid(1), 60), Ok(()));
+            assert_eq!(contract.get_auction_deposit(1, accounts.bob), 160);
+            assert_eq!(
+                contract.withdraw_bid(1),
+                Err(DNSError::HighestBidderCannotWithdraw)
+            );
+
+            // outbid bidders get their deposit back
+            set_next_caller(accounts.charlie);
+            let balance = get_balance(accounts.charlie);
+            assert_eq!(contract.withdraw_bid(1), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), balance + 150);
+            assert_eq!(contract.withdraw_bid(1), Err(DNSError::NothingToWithdraw));
+
+            assert_eq!(contract.settle_auction(1), Err(DNSError::AuctionNotEnded));
+            set_block_timestamp(10);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.bid(1), 200),
+                Err(DNSError::AuctionEnded)
+            );
+
+            assert_eq!(contract.settle_auction(1), Ok(()));
+            assert_eq!(contract.settle_auction(1), Err(DNSError::AuctionSettled));
+            assert_eq!(contract.owner_of(Id::U32(1)), Some(accounts.bob));
+            assert_eq!(contract.resolve(name.clone()), Some(accounts.bob));
+            assert_eq!(contract.get_expiry(1), Some(10 + YEAR));
+            assert_eq!(contract.get_treasury(), 160);
+            assert_eq!(contract.get_auction_deposit(1, accounts.bob), 0);
+            assert_eq!(contract.get_auction_by_name(name), None);
+        }
+
+        #[ink::test]
+        fn sealed_auction_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            let secret = Hash::from([9u8; 32]);
+
+            set_next_caller(accounts.alice);
+            assert_eq!(
+                contract.start_auction(String::from("secret.dot"), AuctionKind::Sealed, 100, 10),
+                Ok(1)
+            );
+
+            // deposits can exceed the sealed amount to hide it
+            for (bidder, amount, deposit) in [
+                (accounts.bob, 200, 300),
+                (accounts.charlie, 150, 150),
+                (accounts.django, 250, 100),
+            ] {
+                set_balance(bidder, 1_000);
+                set_next_caller(bidder);
+                let commitment = contract.make_bid_commitment(1, amount, secret, bidder);
+                assert_eq!(
+                    ink::env::pay_with_call!(contract.sealed_bid(1, commitment), deposit),
+                    Ok(())
+                );
+            }
+            assert_eq!(
+                ink::env::pay_with_call!(contract.bid(1), 500),
+                Err(DNSError::WrongAuctionKind)
+            );
+            assert_eq!(contract.withdraw_bid(1), Err(DNSError::AuctionNotEnded));
+            assert_eq!(
+                contract.reveal_bid(1, 250, secret),
+                Err(DNSError::AuctionNotEnded)
+            );
+
+            set_block_timestamp(10);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.sealed_bid(1, Hash::from([0u8; 32])), 10),
+                Err(DNSError::AuctionEnded)
+            );
+            assert_eq!(
+                contract.reveal_bid(1, 250, secret),
+                Err(DNSError::InsufficientPayment)
+            );
+
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.reveal_bid(1, 300, secret),
+                Err(DNSError::InvalidBidReveal)
+            );
+            assert_eq!(contract.reveal_bid(1, 200, secret), Ok(()));
+            assert_eq!(
+                contract.withdraw_bid(1),
+                Err(DNSError::HighestBidderCannotWithdraw)
+            );
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(contract.reveal_bid(1, 150, secret), Ok(()));
+            assert_eq!(contract.withdraw_bid(1), Ok(()));
+            assert_eq!(contract.settle_auction(1), Err(DNSError::AuctionNotEnded));
+
+            set_block_timestamp(10 + REVEAL_PERIOD);
+            set_next_caller(accounts.django);
+            assert_eq!(
+                contract.reveal_bid(1, 250, secret),
+                Err(DNSError::AuctionEnded)
+            );
+            assert_eq!(contract.settle_auction(1), Ok(()));
+            assert_eq!(contract.owner_of(Id::U32(1)), Some(accounts.bob));
+            assert_eq!(contract.get_treasury(), 200);
+
+            // the winner and unrevealed bidders get what is left of their deposit
+            let balance = get_balance(accounts.django);
+            assert_eq!(contract.withdraw_bid(1), Ok(()));
+            assert_eq!(get_balance(accounts.django), balance + 100);
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.get_auction_deposit(1, accounts.bob), 100);
+            assert_eq!(contract.withdraw_bid(1), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 1_000 - 200);
+        }
+
+        #[ink::test]
+        fn auction_without_bids_settles() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            let name = String::from("premium.dot");
+
+            set_next_caller(accounts.alice);
+            assert_eq!(
+                contract.start_auction(name.clone(), AuctionKind::Open, 100, 10),
+                Ok(1)
+            );
+
+            set_block_timestamp(10);
+            assert_eq!(contract.settle_auction(1), Ok(()));
+            assert_eq!(
+                contract
+                    .get_auction(1)
+                    .map(|auction| auction.highest_bidder),
+                Some(None)
+            );
+            assert_eq!(contract.get_treasury(), 0);
+            assert_eq!(contract.resolve(name.clone()), None);
+
+            // the name can be registered or auctioned again
+            assert_eq!(contract.get_auction_by_name(name.clone()), None);
+            assert_eq!(
+                contract.start_auction(name, AuctionKind::Open, 100, 20),
+                Ok(2)
+            );
+        }
+
+        #[ink::test]
+        fn set_fee_schedule_works() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.set_fee_schedule(Vec::from([100]), 50, 0),
+                Err(DNSError::CallerIsNotOwner)
+            );
+
+            set_next_caller(accounts.alice);
+            assert_eq!(
+                contract.set_fee_schedule(Vec::new(), 50, 0),
+                Err(DNSError::InvalidFeeSchedule)
+            );
+            assert_eq!(
+                contract.set_fee_schedule(Vec::from([100]), 50, 10_001),
+                Err(DNSError::InvalidFeeSchedule)
+            );
+            assert_eq!(
+                contract.set_fee_schedule(Vec::from([1_000, 500, 300, 100]), 50, 500),
+                Ok(())
+            );
+            assert_eq!(
+                contract.get_fee_schedule(),
+                FeeSchedule {
+                    length_prices: Vec::from([1_000, 500, 300, 100]),
+                    renewal_price: 50,
+                    sale_fee_bps: 500,
+                }
+            );
+        }
+
+        #[ink::test]
+        fn registration_price_depends_on_length() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+
+            // a free name takes no payment
+            set_balance(accounts.bob, 1_000);
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                ink::env::pay_with_call!(
+                    contract.create_new_dns(String::from("abc.dot"), State::NotOffering, 0),
+                    10
+                ),
+                Err(DNSError::InexactPayment)
+            );
+
+            set_next_caller(accounts.alice);
+            assert_eq!(
+                contract.set_fee_schedule(Vec::from([1_000, 500, 300, 100]), 50, 0),
+                Ok(())
+            );
+
+            // the last price applies to all longer labels
+            assert_eq!(
+                contract.get_registration_price(String::from("abc.dot")),
+                Ok(300)
+            );
+            assert_eq!(
+                contract.get_registration_price(String::from("abcd.dot")),
+                Ok(100)
+            );
+            assert_eq!(
+                contract.get_registration_price(String::from("abcdefgh.dot")),
+                Ok(100)
+            );
+            assert_eq!(
+                contract.get_registration_price(String::from("ab.dot")),
+                Err(DNSError::NameTooShort)
+            );
+
+            set_balance(accounts.bob, 2_000);
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                ink::env::pay_with_call!(
+                    contract.create_new_dns(String::from("abc.dot"), State::NotOffering, 0),
+                    200
+                ),
+                Err(DNSError::InsufficientPayment)
+            );
+            // paying for a part of a year is rejected instead of kept
+            assert_eq!(
+                ink::env::pay_with_call!(
+                    contract.create_new_dns(String::from("abc.dot"), State::NotOffering, 0),
+                    450
+                ),
+                Err(DNSError::InexactPayment)
+            );
+            assert_eq!(
+                ink::env::pay_with_call!(
+                    contract.create_new_dns(String::from("abc.dot"), State::NotOffering, 0),
+                    300
+                ),
+                Ok(())
+            );
+            assert_eq!(contract.get_expiry(1), Some(YEAR));
+
+            // renewals use the renewal price regardless of length
+            assert_eq!(
+                ink::env::pay_with_call!(contract.renew(1), 120),
+                Err(DNSError::InexactPayment)
+            );
+            assert_eq!(ink::env::pay_with_call!(contract.renew(1), 100), Ok(()));
+            assert_eq!(contract.get_expiry(1), Some(3 * YEAR));
+            assert_eq!(contract.get_treasury(), 400);
+        }
+
+        #[ink::test]
+        fn sale_fee_goes_to_treasury() {
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            assert_eq!(contract.set_fee_schedule(Vec::from([0]), 0, 1_000), Ok(()));
+            create_name(&mut contract, accounts.bob, "bob.dot");
+
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.set_offer(1, State::PublicOffering, 100, None),
+                Ok(())
+            );
+
+            set_balance(accounts.bob, 0);
+            set_balance(accounts.charlie, 100);
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.buy_domain(1), 100),
+                Ok(())
+            );
+
+            assert_eq!(get_balance(accounts.bob), 90);
+            assert_eq!(contract.get_treasury(), 10);
+        }
+
+        #[ink::test]
+        fn withdraw_works() {
+            type Event = <DnsContract as ::ink::reflect::ContractEventBase>::Type;
+
+            set_next_caller(default_accounts().alice);
+            let mut contract = DnsContract::new(100);
+            let accounts = default_accounts();
+
+            set_balance(accounts.bob, 1_000);
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                ink::env::pay_with_call!(
+                    contract.create_new_dns(String::from("bob.dot"), State::NotOffering, 0),
+                    200
+                ),
+                Ok(())
+            );
+            assert_eq!(
+                contract.withdraw(accounts.bob, 100),
+                Err(DNSError::CallerIsNotOwner)
+            );
+
+            set_balance(accounts.django, 0);
+            set_next_caller(accounts.alice);
+            assert_eq!(
+                contract.withdraw(accounts.django, 300),
+                Err(DNSError::InsufficientTreasury)
+            );
+            assert_eq!(contract.withdraw(accounts.django, 150), Ok(()));
+            assert_eq!(get_balance(accounts.django), 150);
+            assert_eq!(contract.get_treasury(), 50);
+
+            let event = ink::env::test::recorded_events()
+                .last()
+                .expect("no event was emitted");
+            let event = <Event as scale::Decode>::decode(&mut &event.data[..])
+                .expect("encountered invalid contract event data buffer");
+            assert!(matches!(
+                event,
+                Event::Withdrawn(Withdrawn { to, amount: 150 }) if to == accounts.django
+            ));
+        }
+
+        #[ink::test]
+        fn lifecycle_events_work() {
+            type Event = <DnsContract as ::ink::reflect::ContractEventBase>::Type;
+
+            let mut contract = create_contract();
+            let accounts = default_accounts();
+            let name = String::from("bob.dot");
+            create_name(&mut contract, accounts.bob, "bob.dot");
+
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.set_new_owner(1, accounts.charlie), Ok(()));
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_offer(1, State::PublicOffering, 50, None),
+                Ok(())
+            );
+            set_balance(accounts.django, 100);
+            set_next_caller(accounts.django);
+            assert_eq!(ink::env::pay_with_call!(contract.buy_domain(1), 50), Ok(()));
+            assert_eq!(ink::env::pay_with_call!(contract.renew(1), 0), Ok(()));
+
+            // the name is released to a new owner once the grace period is over
+            set_block_timestamp(2 * YEAR + GRACE_PERIOD);
+            create_name(&mut contract, accounts.eve, "bob.dot");
+
+            let recorded: Vec<_> = ink::env::test::recorded_events().collect();
+            // the event signature plus the id, name and owner topics
+            assert_eq!(recorded[0].topics.len(), 4);
+
+            let events: Vec<Event> = recorded
+                .into_iter()
+                .map(|event| {
+                    <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                })
+                .collect();
+
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::NewNameClaimed(NewNameClaimed { name_id: 1, name: n, owner, expires_at })
+                    if *n == name && *owner == accounts.bob && *expires_at == YEAR
+            )));
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::SetNewOwner(SetNewOwner { name_id: 1, name: n, old_owner, new_owner })
+                    if *n == name && *old_owner == accounts.bob && *new_owner == accounts.charlie
+            )));
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::DomainSold(DomainSold { name_id: 1, name: n, seller, buyer, price: 50 })
+                    if *n == name && *seller == accounts.charlie && *buyer == accounts.django
+            )));
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::Renewed(Renewed { name_id: 1, name: n, expires_at })
+                    if *n == name && *expires_at == 2 * YEAR
+            )));
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::Expired(Expired { name_id: 1, name: n, owner })
+                    if *n == name && *owner == accounts.django
+            )));
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::NewNameClaimed(NewNameClaimed { name_id: 1, owner, .. })
+                    if *owner == accounts.eve
+            )));
+        }
+    }
+
+    /// This is how you'd write end-to-end (E2E) or integration tests for ink! contracts.
+    ///
+    /// When running these you need to make sure that you:
+    /// - Compile the tests with the `e2e-tests` feature flag enabled (`--features e2e-tests`)
+    /// - Are running a Substrate node which contains `pallet-contracts` in the background
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        /// Imports all the definations from the outer scope so we can use theme here.
+        use super::*;
+
+        /// A helper function used for calling contract message
+        use ink_e2e::build_message;
+
+        /// The End-to-End test `Result` type.
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        /// We test that we can upload and instantiate the contract.
+        #[ink_e2e::test]
+        async fn new_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let constructor = DnsContractRef::new(0);
+
+            // When
+            let contract_account_id = client
+                .instantiate("dns_contract", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            // Then
+            let get_claimed = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.get_no_of_name_claimed());
+            let get_claimed_result = client
+                .call_dry_run(&ink_e2e::alice(), &get_claimed, 0, None)
+                .await;
+
+            assert_eq!(get_claimed_result.return_value(), 0);
+
+            Ok(())
+        }
+
+        /// We test that a name can't be revealed right after committing to it.
+        #[ink_e2e::test]
+        async fn commit_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let constructor = DnsContractRef::new(0);
+            let contract_account_id = client
+                .instantiate("dns_contract", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let name = String::from("bob.dot");
+            let secret = Hash::from([7u8; 32]);
+            let bob = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+
+            let make_commitment = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.make_commitment(name.clone(), secret, bob));
+            let commitment = client
+                .call_dry_run(&ink_e2e::bob(), &make_commitment, 0, None)
+                .await
+                .return_value();
+
+            // When
+            let commit = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.commit(commitment));
+            client
+                .call(&ink_e2e::bob(), commit, 0, None)
+                .await
+                .expect("calling `commit` failed");
+
+            // Then
+            let get_commitment = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.get_commitment(commitment));
+            let get_commitment_result = client
+                .call_dry_run(&ink_e2e::bob(), &get_commitment, 0, None)
+                .await;
+            assert!(get_commitment_result.return_value().is_some());
+
+            let reveal = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.reveal(name.clone(), secret, State::NotOffering, 0));
+            let reveal_result = client.call_dry_run(&ink_e2e::bob(), &reveal, 0, None).await;
+            assert_eq!(
+                reveal_result.return_value(),
+                Err(DNSError::CommitmentTooNew)
+            );
+
+            Ok(())
+        }
+
+        /// We test that a committed name can be revealed, registered and transferred.
+        #[ink_e2e::test]
+        async fn register_and_transfer_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            // without a commitment age the reveal doesn't have to wait for later blocks
+            let constructor = DnsContractRef::with_commitment_age(0, 0);
+            let contract_account_id = client
+                .instantiate("dns_contract", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let name = String::from("bob.dot");
+            let secret = Hash::from([7u8; 32]);
+            let bob = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let charlie = ink_e2e::account_id(ink_e2e::AccountKeyring::Charlie);
+
+            let make_commitment = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.make_commitment(name.clone(), secret, bob));
+            let commitment = client
+                .call_dry_run(&ink_e2e::bob(), &make_commitment, 0, None)
+                .await
+                .return_value();
+
+            let commit = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.commit(commitment));
+            client
+                .call(&ink_e2e::bob(), commit, 0, None)
+                .await
+                .expect("calling `commit` failed");
+
+            // When
+            let reveal = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.reveal(name.clone(), secret, State::NotOffering, 0));
+            client
+                .call(&ink_e2e::bob(), reveal, 0, None)
+                .await
+                .expect("calling `reveal` failed");
+
+            let set_new_owner = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.set_new_owner(1, charlie));
+            client
+                .call(&ink_e2e::bob(), set_new_owner, 0, None)
+                .await
+                .expect("calling `set_new_owner` failed");
+
+            // Then
+            let is_claimed = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.is_claimed(1));
+            let is_claimed_result = client
+                .call_dry_run(&ink_e2e::alice(), &is_claimed, 0, None)
+                .await;
+            assert!(is_claimed_result.return_value());
+
+            let get_claimed = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.get_no_of_name_claimed());
+            let get_claimed_result = client
+                .call_dry_run(&ink_e2e::alice(), &get_claimed, 0, None)
+                .await;
+            assert_eq!(get_claimed_result.return_value(), 1);
+
+            let bob_count = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.get_owner_name_count(bob));
+            let bob_count_result = client
+                .call_dry_run(&ink_e2e::alice(), &bob_count, 0, None)
+                .await;
+            assert_eq!(bob_count_result.return_value(), 0);
+
+            let charlie_count = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.get_owner_name_count(charlie));
+            let charlie_count_result = client
+                .call_dry_run(&ink_e2e::alice(), &charlie_count, 0, None)
+                .await;
+            assert_eq!(charlie_count_result.return_value(), 1);
+
+            let resolve = build_message::<DnsContractRef>(contract_account_id.clone())
+                .call(|dns| dns.resolve(name.clone()));
+            let resolve_result = client
+                .call_dry_run(&ink_e2e::alice(), &resolve, 0, None)
+                .await;
+            assert_eq!(resolve_result.return_value(), Some(charlie));
+
+            Ok(())
+        }
     }
 }
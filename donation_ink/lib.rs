@@ -3,11 +3,36 @@
 
 #[ink::contract]
 mod donation_ink {
-    use ink::prelude::vec::Vec;
+    use ink::env::call::{build_call, ExecutionInput, Selector};
+    use ink::prelude::{string::String, vec::Vec};
     use ink::storage::Mapping;
 
     pub type DonationId = i32;
 
+    pub type CampaignId = i32;
+
+    pub type PledgeId = i32;
+
+    // number of entries returned by a paginated query at most
+    const MAX_PAGE_SIZE: u32 = 50;
+
+    // length of a donation message in bytes at most
+    const MAX_MESSAGE_LENGTH: usize = 140;
+
+    // number of donors kept on the leaderboard
+    const LEADERBOARD_SIZE: usize = 10;
+
+    // shares of a donation are given in basis points and must add up to the whole
+    const TOTAL_SHARES: u16 = 10_000;
+    const MAX_BENEFICIARIES: usize = 10;
+
+    // default time between proposing new beneficiaries and them taking over, two days
+    const BENEFICIARY_DELAY: Timestamp = 2 * 24 * 60 * 60 * 1000;
+
+    // selectors of the PSP22 `transfer` and `transfer_from` messages
+    const PSP22_TRANSFER_SELECTOR: [u8; 4] = [0xdb, 0x20, 0xf9, 0xf5];
+    const PSP22_TRANSFER_FROM_SELECTOR: [u8; 4] = [0x54, 0xb3, 0xc7, 0x6e];
+
     #[derive(scale::Decode, scale::Encode)]
     #[cfg_attr(
         feature = "std",
@@ -16,6 +41,146 @@ mod donation_ink {
     pub struct Donation {
         account: AccountId,
         amount: u128,
+        campaign: Option<CampaignId>,
+        // PSP22 token the amount is given in, `None` for native balance
+        token: Option<AccountId>,
+        message: Option<String>,
+        // anonymous donors are hidden in public queries
+        anonymous: bool,
+    }
+
+    // public view of a donation on the donor wall, `account` is `None` for anonymous donors
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq)]
+    #[cfg_attr(feature = "std", derive(scale_info::TypeInfo))]
+    pub struct WallEntry {
+        donation_id: DonationId,
+        account: Option<AccountId>,
+        amount: u128,
+        campaign: Option<CampaignId>,
+        token: Option<AccountId>,
+        message: Option<String>,
+    }
+
+    // proof of a donation, minted as a non-transferable token with the donation id as id
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Receipt {
+        donation_id: DonationId,
+        amount: Balance,
+        timestamp: Timestamp,
+        campaign: Option<CampaignId>,
+        token: Option<AccountId>,
+    }
+
+    // beneficiaries proposed by the owner, they can accept from `effective_at` on
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct PendingBeneficiaries {
+        beneficiaries: Vec<(AccountId, u16)>,
+        effective_at: Timestamp,
+    }
+
+    // fundraising campaign, donations are held in escrow until the deadline
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Campaign {
+        beneficiary: AccountId,
+        goal: Balance,
+        deadline: Timestamp,
+        raised: Balance,
+        released: bool,
+    }
+
+    // sponsor funds matching donations, `ratio` is in basis points so 10,000 matches 1:1,
+    // at most `cap` is matched in total
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq, Clone)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct MatchingPool {
+        sponsor: AccountId,
+        ratio: u32,
+        cap: Balance,
+        balance: Balance,
+        matched: Balance,
+    }
+
+    // recurring donation paid from a deposit, one `amount` per `interval`
+    #[derive(Debug, scale::Decode, scale::Encode, Eq, PartialEq)]
+    #[cfg_attr(
+        feature = "std",
+        derive(scale_info::TypeInfo, ink::storage::traits::StorageLayout)
+    )]
+    pub struct Pledge {
+        donor: AccountId,
+        amount: Balance,
+        interval: Timestamp,
+        next_payment: Timestamp,
+        deposit: Balance,
+    }
+
+    /// Errors that can occur upon calling this contract.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum DonationError {
+        NotOwner,
+        ZeroDonation,
+        ZeroGoal,
+        InvalidDeadline,
+        CampaignNotFound,
+        CampaignEnded,
+        CampaignNotEnded,
+        CampaignReleased,
+        GoalNotReached,
+        GoalReached,
+        NothingToRefund,
+        TransferFailed,
+        NoBeneficiaries,
+        TooManyBeneficiaries,
+        DuplicateBeneficiary,
+        ZeroShare,
+        InvalidShareTotal,
+        TokenTransferFailed,
+        ZeroInterval,
+        InsufficientDeposit,
+        PledgeNotFound,
+        NotPledgeDonor,
+        PledgeNotDue,
+        MessageTooLong,
+        NoPendingBeneficiaries,
+        NotPendingBeneficiary,
+        BeneficiaryChangeNotReady,
+        ZeroRatio,
+        MatchingPoolActive,
+        NoMatchingPool,
+        NotSponsor,
+        ContractPaused,
+        ContractNotPaused,
+        DonationTooSmall,
+        DonationTooLarge,
+        InvalidLimits,
+    }
+
+    /// Errors returned by a PSP22 token contract.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum PSP22Error {
+        Custom(String),
+        InsufficientBalance,
+        InsufficientAllowance,
+        ZeroRecipientAddress,
+        ZeroSenderAddress,
+        SafeTransferCheckFailed(String),
     }
 
     // Donation struct default implementations
@@ -24,95 +189,1093 @@ mod donation_ink {
             Self {
                 account: zero_address(),
                 amount: 0,
+                campaign: None,
+                token: None,
+                message: None,
+                anonymous: false,
             }
         }
     }
 
+    // `donor` is `None` for anonymous donations, `beneficiary` is the campaign's
+    // beneficiary or the first beneficiary of the split.
+    // A failed forward reverts the donation along with its events, so there is no
+    // separate forwarding failure event, the caller gets `TransferFailed` instead
+    #[ink(event)]
+    pub struct Donated {
+        #[ink(topic)]
+        donor: Option<AccountId>,
+        #[ink(topic)]
+        beneficiary: AccountId,
+        #[ink(topic)]
+        donation_id: DonationId,
+        amount: Balance,
+        campaign: Option<CampaignId>,
+        token: Option<AccountId>,
+    }
+
+    // `old` and `new` are the first beneficiaries of the previous and the new split
+    #[ink(event)]
+    pub struct BeneficiaryChanged {
+        #[ink(topic)]
+        old: AccountId,
+        #[ink(topic)]
+        new: AccountId,
+        beneficiaries: Vec<(AccountId, u16)>,
+    }
+
+    #[ink(event)]
+    pub struct BeneficiariesProposed {
+        #[ink(topic)]
+        beneficiary: AccountId,
+        beneficiaries: Vec<(AccountId, u16)>,
+        effective_at: Timestamp,
+    }
+
+    #[ink(event)]
+    pub struct BeneficiariesProposalCancelled {
+        #[ink(topic)]
+        beneficiary: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct DonationLimitsChanged {
+        min_donation: Balance,
+        max_donation: Option<Balance>,
+    }
+
+    #[ink(event)]
+    pub struct Paused {
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    #[ink(event)]
+    pub struct Unpaused {
+        #[ink(topic)]
+        by: AccountId,
+    }
+
+    // PSP34 token events
+    #[ink(event)]
+    pub struct Transfer {
+        #[ink(topic)]
+        from: Option<AccountId>,
+        #[ink(topic)]
+        to: Option<AccountId>,
+        #[ink(topic)]
+        id: Id,
+    }
+
+    /// PSP34 token id, receipts are minted as `Id::U32` with their `DonationId`.
+    #[derive(Debug, PartialEq, Eq, Clone, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum Id {
+        U8(u8),
+        U16(u16),
+        U32(u32),
+        U64(u64),
+        U128(u128),
+        Bytes(Vec<u8>),
+    }
+
+    impl From<DonationId> for Id {
+        fn from(donation_id: DonationId) -> Self {
+            Id::U32(donation_id as u32)
+        }
+    }
+
+    impl Id {
+        // the donation a token id refers to, any integer variant with the same value matches
+        fn donation_id(&self) -> Option<DonationId> {
+            match self {
+                Id::U8(id) => Some(DonationId::from(*id)),
+                Id::U16(id) => Some(DonationId::from(*id)),
+                Id::U32(id) => DonationId::try_from(*id).ok(),
+                Id::U64(id) => DonationId::try_from(*id).ok(),
+                Id::U128(id) => DonationId::try_from(*id).ok(),
+                Id::Bytes(_) => None,
+            }
+        }
+    }
+
+    /// Errors that can occur upon calling the PSP34 interface of this contract.
+    #[derive(Debug, PartialEq, Eq, scale::Encode, scale::Decode)]
+    #[cfg_attr(feature = "std", derive(::scale_info::TypeInfo))]
+    pub enum PSP34Error {
+        Custom(String),
+        SelfApprove,
+        NotApproved,
+        TokenExists,
+        TokenNotExists,
+        SafeTransferCheckFailed(String),
+    }
+
+    // PSP34 non-fungible token interface, every receipt is a token with its `DonationId` as id
+    #[ink::trait_definition]
+    pub trait PSP34 {
+        #[ink(message)]
+        fn collection_id(&self) -> Id;
+
+        #[ink(message)]
+        fn balance_of(&self, owner: AccountId) -> u32;
+
+        #[ink(message)]
+        fn owner_of(&self, id: Id) -> Option<AccountId>;
+
+        #[ink(message)]
+        fn allowance(&self, owner: AccountId, operator: AccountId, id: Option<Id>) -> bool;
+
+        #[ink(message)]
+        fn approve(
+            &mut self,
+            operator: AccountId,
+            id: Option<Id>,
+            approved: bool,
+        ) -> Result<(), PSP34Error>;
+
+        #[ink(message)]
+        fn transfer(&mut self, to: AccountId, id: Id, data: Vec<u8>) -> Result<(), PSP34Error>;
+
+        #[ink(message)]
+        fn total_supply(&self) -> Balance;
+    }
+
     #[ink(storage)]
     pub struct DonationContract {
         owner: AccountId,
-        beneficiary: AccountId,
+        // recipients of every donation with their share in basis points
+        beneficiaries: Vec<(AccountId, u16)>,
+        // a change of beneficiaries waiting for its delay and acceptance
+        pending_beneficiaries: Option<PendingBeneficiaries>,
+        beneficiary_delay: Timestamp,
         donations: Mapping<DonationId, Donation>,
         donation_id: i32,
+        campaigns: Mapping<CampaignId, Campaign>,
+        // escrowed amount of each donor per campaign
+        contributions: Mapping<(CampaignId, AccountId), Balance>,
+        campaign_id: CampaignId,
+        // lifetime totals and number of donations per donor
+        donor_totals: Mapping<AccountId, u128>,
+        donor_counts: Mapping<AccountId, u32>,
+        // biggest donors by lifetime total, highest first
+        leaderboard: Vec<(AccountId, u128)>,
+        // highest total cut off the full leaderboard, no donor off the board has more
+        leaderboard_floor: u128,
+        // PSP22 donations per token and donor, and per token overall
+        token_donations: Mapping<(AccountId, AccountId), Balance>,
+        token_totals: Mapping<AccountId, Balance>,
+        pledges: Mapping<PledgeId, Pledge>,
+        pledge_id: PledgeId,
+        // donation receipts, the receipts of a donor by position and their number
+        receipts: Mapping<DonationId, Receipt>,
+        receipt_owner: Mapping<DonationId, AccountId>,
+        owned_receipts: Mapping<(AccountId, u32), DonationId>,
+        receipt_count: Mapping<AccountId, u32>,
+        total_receipts: u32,
+        matching_pool: Option<MatchingPool>,
+        // limits of a single native donation, no maximum when `None`
+        min_donation: Balance,
+        max_donation: Option<Balance>,
+        // paused contracts reject donations, refunds and withdrawals still work
+        paused: bool,
+    }
+
+    impl DonationContract {
+        #[ink(constructor)]
+        pub fn new(beneficiary: AccountId) -> Self {
+            Self::with_delay(beneficiary, BENEFICIARY_DELAY)
+        }
+
+        // the delay of beneficiary changes is fixed at deployment so a compromised
+        // owner key can't shorten it
+        #[ink(constructor)]
+        pub fn with_delay(beneficiary: AccountId, beneficiary_delay: Timestamp) -> Self {
+            let owner = Self::env().caller();
+            Self {
+                owner,
+                beneficiaries: Vec::from([(beneficiary, TOTAL_SHARES)]),
+                pending_beneficiaries: None,
+                beneficiary_delay,
+                donations: Mapping::default(),
+                donation_id: 1,
+                campaigns: Mapping::default(),
+                contributions: Mapping::default(),
+                campaign_id: 1,
+                donor_totals: Mapping::default(),
+                donor_counts: Mapping::default(),
+                leaderboard: Vec::new(),
+                leaderboard_floor: 0,
+                token_donations: Mapping::default(),
+                token_totals: Mapping::default(),
+                pledges: Mapping::default(),
+                pledge_id: 1,
+                receipts: Mapping::default(),
+                receipt_owner: Mapping::default(),
+                owned_receipts: Mapping::default(),
+                receipt_count: Mapping::default(),
+                total_receipts: 0,
+                matching_pool: None,
+                min_donation: 0,
+                max_donation: None,
+                paused: false,
+            }
+        }
+
+        // the first beneficiary, which also receives the rounding dust of every split
+        #[ink(message)]
+        pub fn get_beneficiary(&self) -> Option<AccountId> {
+            self.beneficiaries.first().map(|(account, _)| *account)
+        }
+
+        #[ink(message)]
+        pub fn get_beneficiaries(&self) -> Vec<(AccountId, u16)> {
+            self.beneficiaries.clone()
+        }
+
+        #[ink(message)]
+        pub fn get_pending_beneficiaries(&self) -> Option<PendingBeneficiaries> {
+            self.pending_beneficiaries.clone()
+        }
+
+        // limit the amount of a single native donation, only by the owner
+        #[ink(message)]
+        pub fn set_donation_limits(
+            &mut self,
+            min_donation: Balance,
+            max_donation: Option<Balance>,
+        ) -> Result<(), DonationError> {
+            if self.env().caller() != self.owner {
+                return Err(DonationError::NotOwner);
+            }
+            if max_donation.is_some_and(|max_donation| max_donation < min_donation.max(1)) {
+                return Err(DonationError::InvalidLimits);
+            }
+
+            self.min_donation = min_donation;
+            self.max_donation = max_donation;
+            self.env().emit_event(DonationLimitsChanged {
+                min_donation,
+                max_donation,
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_donation_limits(&self) -> (Balance, Option<Balance>) {
+            (self.min_donation, self.max_donation)
+        }
+
+        // stop accepting donations during an incident, only by the owner
+        #[ink(message)]
+        pub fn pause(&mut self) -> Result<(), DonationError> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(DonationError::NotOwner);
+            }
+            self.ensure_not_paused()?;
+
+            self.paused = true;
+            self.env().emit_event(Paused { by: caller });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn unpause(&mut self) -> Result<(), DonationError> {
+            let caller = self.env().caller();
+            if caller != self.owner {
+                return Err(DonationError::NotOwner);
+            }
+            if !self.paused {
+                return Err(DonationError::ContractNotPaused);
+            }
+
+            self.paused = false;
+            self.env().emit_event(Unpaused { by: caller });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn is_paused(&self) -> bool {
+            self.paused
+        }
+
+        fn ensure_not_paused(&self) -> Result<(), DonationError> {
+            if self.paused {
+                return Err(DonationError::ContractPaused);
+            }
+            Ok(())
+        }
+
+        // check a native donation amount against the pause switch and the limits
+        fn check_donation(&self, amount: Balance) -> Result<(), DonationError> {
+            self.ensure_not_paused()?;
+            if amount == 0 {
+                return Err(DonationError::ZeroDonation);
+            }
+            if amount < self.min_donation {
+                return Err(DonationError::DonationTooSmall);
+            }
+            if self
+                .max_donation
+                .is_some_and(|max_donation| amount > max_donation)
+            {
+                return Err(DonationError::DonationTooLarge);
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn get_beneficiary_delay(&self) -> Timestamp {
+            self.beneficiary_delay
+        }
+
+        // propose a new split of donations, only by the owner, it replaces any
+        // earlier proposal and takes effect once accepted after the delay
+        #[ink(message)]
+        pub fn propose_beneficiaries(
+            &mut self,
+            beneficiaries: Vec<(AccountId, u16)>,
+        ) -> Result<(), DonationError> {
+            if self.env().caller() != self.owner {
+                return Err(DonationError::NotOwner);
+            }
+            validate_beneficiaries(&beneficiaries)?;
+
+            let effective_at = self
+                .env()
+                .block_timestamp()
+                .saturating_add(self.beneficiary_delay);
+            self.env().emit_event(BeneficiariesProposed {
+                beneficiary: beneficiaries[0].0,
+                beneficiaries: beneficiaries.clone(),
+                effective_at,
+            });
+            self.pending_beneficiaries = Some(PendingBeneficiaries {
+                beneficiaries,
+                effective_at,
+            });
+            Ok(())
+        }
+
+        // propose a single beneficiary receiving every donation in full
+        #[ink(message)]
+        pub fn propose_beneficiary(
+            &mut self,
+            new_beneficiary: AccountId,
+        ) -> Result<(), DonationError> {
+            self.propose_beneficiaries(Vec::from([(new_beneficiary, TOTAL_SHARES)]))
+        }
+
+        // take over the proposed split, only by its first beneficiary once the delay passed
+        #[ink(message)]
+        pub fn accept_beneficiaries(&mut self) -> Result<(), DonationError> {
+            let pending = self
+                .pending_beneficiaries
+                .clone()
+                .ok_or(DonationError::NoPendingBeneficiaries)?;
+
+            if Some(self.env().caller())
+                != pending.beneficiaries.first().map(|(account, _)| *account)
+            {
+                return Err(DonationError::NotPendingBeneficiary);
+            }
+            if self.env().block_timestamp() < pending.effective_at {
+                return Err(DonationError::BeneficiaryChangeNotReady);
+            }
+
+            let old = self.beneficiaries[0].0;
+            self.beneficiaries = pending.beneficiaries;
+            self.pending_beneficiaries = None;
+
+            self.env().emit_event(BeneficiaryChanged {
+                old,
+                new: self.beneficiaries[0].0,
+                beneficiaries: self.beneficiaries.clone(),
+            });
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn cancel_beneficiaries_proposal(&mut self) -> Result<(), DonationError> {
+            if self.env().caller() != self.owner {
+                return Err(DonationError::NotOwner);
+            }
+            let pending = self
+                .pending_beneficiaries
+                .take()
+                .ok_or(DonationError::NoPendingBeneficiaries)?;
+
+            self.env().emit_event(BeneficiariesProposalCancelled {
+                beneficiary: pending.beneficiaries[0].0,
+            });
+            Ok(())
+        }
+
+        #[ink(message, payable)]
+        pub fn donation(&mut self) -> Result<(), DonationError> {
+            self.donate_with_message(None, false)
+        }
+
+        // donate with an optional public message, anonymous donations don't show
+        // the donor in public queries
+        #[ink(message, payable)]
+        pub fn donate_with_message(
+            &mut self,
+            message: Option<String>,
+            anonymous: bool,
+        ) -> Result<(), DonationError> {
+            // Account who is donating
+            let caller = self.env().caller();
+
+            // Donation amount
+            let donation_amount = self.env().transferred_value();
+            self.check_donation(donation_amount)?;
+            if message
+                .as_ref()
+                .is_some_and(|message| message.len() > MAX_MESSAGE_LENGTH)
+            {
+                return Err(DonationError::MessageTooLong);
+            }
+
+            self.record_donation(Donation {
+                account: caller,
+                amount: donation_amount,
+                campaign: None,
+                token: None,
+                message,
+                anonymous,
+            });
+            // anonymous donations don't count towards the donor's total or rank
+            if !anonymous {
+                self.add_donor_amount(caller, donation_amount);
+            }
+
+            // Send donation amount and its match to the beneficiary accounts, a failed
+            // transfer reverts the whole donation
+            let matched = self.take_match(donation_amount);
+            for (beneficiary, amount) in
+                split_amount(&self.beneficiaries, donation_amount + matched)
+            {
+                self.env()
+                    .transfer(beneficiary, amount)
+                    .map_err(|_| DonationError::TransferFailed)?;
+            }
+            Ok(())
+        }
+
+        // fund a matching pool with the transferred value, while it has funds left
+        // every donation is matched at `ratio` basis points up to `cap` in total
+        #[ink(message, payable)]
+        pub fn create_matching_pool(
+            &mut self,
+            ratio: u32,
+            cap: Balance,
+        ) -> Result<(), DonationError> {
+            let deposit = self.env().transferred_value();
+
+            if self.remaining_matching() > 0 {
+                return Err(DonationError::MatchingPoolActive);
+            }
+            if ratio == 0 {
+                return Err(DonationError::ZeroRatio);
+            }
+            if deposit == 0 {
+                return Err(DonationError::ZeroDonation);
+            }
+
+            // hand what is left of an exhausted pool back to its sponsor
+            if let Some(pool) = self.matching_pool.take() {
+                if pool.balance > 0 {
+                    self.env()
+                        .transfer(pool.sponsor, pool.balance)
+                        .map_err(|_| DonationError::TransferFailed)?;
+                }
+            }
+
+            self.matching_pool = Some(MatchingPool {
+                sponsor: self.env().caller(),
+                ratio,
+                cap,
+                balance: deposit,
+                matched: 0,
+            });
+            Ok(())
+        }
+
+        // close the matching pool and give the unused funds back to the sponsor
+        #[ink(message)]
+        pub fn withdraw_matching_pool(&mut self) -> Result<(), DonationError> {
+            let pool = self
+                .matching_pool
+                .clone()
+                .ok_or(DonationError::NoMatchingPool)?;
+
+            if self.env().caller() != pool.sponsor {
+                return Err(DonationError::NotSponsor);
+            }
+
+            self.matching_pool = None;
+            self.env()
+                .transfer(pool.sponsor, pool.balance)
+                .map_err(|_| DonationError::TransferFailed)
+        }
+
+        #[ink(message)]
+        pub fn get_matching_pool(&self) -> Option<MatchingPool> {
+            self.matching_pool.clone()
+        }
+
+        // amount that can still be matched, limited by both the pool funds and the cap
+        #[ink(message)]
+        pub fn remaining_matching(&self) -> Balance {
+            self.matching_pool.as_ref().map_or(0, |pool| {
+                pool.balance.min(pool.cap.saturating_sub(pool.matched))
+            })
+        }
+
+        // take the match of a donation out of the pool
+        fn take_match(&mut self, amount: Balance) -> Balance {
+            let remaining = self.remaining_matching();
+            let Some(pool) = self.matching_pool.as_mut() else {
+                return 0;
+            };
+
+            let matched = (amount.saturating_mul(Balance::from(pool.ratio))
+                / Balance::from(TOTAL_SHARES))
+            .min(remaining);
+            pool.balance -= matched;
+            pool.matched += matched;
+            matched
+        }
+
+        // donate `amount` of a PSP22 token, the caller must have approved this contract
+        // to spend it, the tokens are pulled in and forwarded to the beneficiaries
+        #[ink(message)]
+        pub fn donate_token(
+            &mut self,
+            token: AccountId,
+            amount: Balance,
+        ) -> Result<(), DonationError> {
+            let caller = self.env().caller();
+            self.ensure_not_paused()?;
+            if amount == 0 {
+                return Err(DonationError::ZeroDonation);
+            }
+
+            build_call::<Environment>()
+                .call(token)
+                .exec_input(
+                    ExecutionInput::new(Selector::new(PSP22_TRANSFER_FROM_SELECTOR))
+                        .push_arg(caller)
+                        .push_arg(self.env().account_id())
+                        .push_arg(amount)
+                        .push_arg(Vec::<u8>::new()),
+                )
+                .returns::<Result<(), PSP22Error>>()
+                .try_invoke()
+                .map_err(|_| DonationError::TokenTransferFailed)?
+                .map_err(|_| DonationError::TokenTransferFailed)?
+                .map_err(|_| DonationError::TokenTransferFailed)?;
+
+            self.record_donation(Donation {
+                account: caller,
+                amount,
+                campaign: None,
+                token: Some(token),
+                message: None,
+                anonymous: false,
+            });
+
+            let donated = self.donated_token_by(token, caller);
+            self.token_donations
+                .insert((token, caller), &(donated + amount));
+            let total = self.get_token_total(token);
+            self.token_totals.insert(token, &(total + amount));
+
+            for (beneficiary, part) in split_amount(&self.beneficiaries, amount) {
+                build_call::<Environment>()
+                    .call(token)
+                    .exec_input(
+                        ExecutionInput::new(Selector::new(PSP22_TRANSFER_SELECTOR))
+                            .push_arg(beneficiary)
+                            .push_arg(part)
+                            .push_arg(Vec::<u8>::new()),
+                    )
+                    .returns::<Result<(), PSP22Error>>()
+                    .try_invoke()
+                    .map_err(|_| DonationError::TokenTransferFailed)?
+                    .map_err(|_| DonationError::TokenTransferFailed)?
+                    .map_err(|_| DonationError::TokenTransferFailed)?;
+            }
+            Ok(())
+        }
+
+        #[ink(message)]
+        pub fn donated_token_by(&self, token: AccountId, account: AccountId) -> Balance {
+            self.token_donations
+                .get((token, account))
+                .unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_token_total(&self, token: AccountId) -> Balance {
+            self.token_totals.get(token).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn get_donation_amount_by_user(&self, donation_id: DonationId) -> (AccountId, u128) {
+            let donation = self.donations.get(donation_id).unwrap_or_default();
+            if donation.anonymous {
+                return (zero_address(), donation.amount);
+            }
+            (donation.account, donation.amount)
+        }
+
+        // donations in the order they were made, starting at position `offset`
+        #[ink(message)]
+        pub fn donor_wall(&self, offset: u32, limit: u32) -> Vec<WallEntry> {
+            let first = DonationId::try_from(offset)
+                .unwrap_or(DonationId::MAX)
+                .saturating_add(1);
+            let last = first
+                .saturating_add(limit.min(MAX_PAGE_SIZE) as DonationId)
+                .min(self.donation_id);
+
+            (first..last)
+                .filter_map(|donation_id| {
+                    self.donations.get(donation_id).map(|donation| WallEntry {
+                        donation_id,
+                        account: (!donation.anonymous).then_some(donation.account),
+                        amount: donation.amount,
+                        campaign: donation.campaign,
+                        token: donation.token,
+                        message: donation.message,
+                    })
+                })
+                .collect()
+        }
+
+        // start a new campaign, only by the owner
+        #[ink(message)]
+        pub fn create_campaign(
+            &mut self,
+            beneficiary: AccountId,
+            goal: Balance,
+            deadline: Timestamp,
+        ) -> Result<CampaignId, DonationError> {
+            if self.env().caller() != self.owner {
+                return Err(DonationError::NotOwner);
+            }
+            if goal == 0 {
+                return Err(DonationError::ZeroGoal);
+            }
+            if deadline <= self.env().block_timestamp() {
+                return Err(DonationError::InvalidDeadline);
+            }
+
+            let campaign_id = self.next_campaign_id();
+            self.campaigns.insert(
+                campaign_id,
+                &Campaign {
+                    beneficiary,
+                    goal,
+                    deadline,
+                    raised: 0,
+                    released: false,
+                },
+            );
+            Ok(campaign_id)
+        }
+
+        // donate the transferred value to a running campaign, it stays in the contract
+        #[ink(message, payable)]
+        pub fn donate_to_campaign(&mut self, campaign_id: CampaignId) -> Result<(), DonationError> {
+            let mut campaign = self
+                .campaigns
+                .get(campaign_id)
+                .ok_or(DonationError::CampaignNotFound)?;
+            let caller = self.env().caller();
+            let amount = self.env().transferred_value();
+
+            if campaign.released {
+                return Err(DonationError::CampaignReleased);
+            }
+            if self.env().block_timestamp() >= campaign.deadline {
+                return Err(DonationError::CampaignEnded);
+            }
+            self.check_donation(amount)?;
+
+            campaign.raised += amount;
+            self.campaigns.insert(campaign_id, &campaign);
+
+            let contributed = self
+                .contributions
+                .get((campaign_id, caller))
+                .unwrap_or_default();
+            self.contributions
+                .insert((campaign_id, caller), &(contributed + amount));
+
+            self.record_donation(Donation {
+                account: caller,
+                amount,
+                campaign: Some(campaign_id),
+                token: None,
+                message: None,
+                anonymous: false,
+            });
+            self.add_donor_amount(caller, amount);
+            Ok(())
+        }
+
+        // pay the escrowed funds out to the beneficiary once the goal is met
+        #[ink(message)]
+        pub fn release_campaign(&mut self, campaign_id: CampaignId) -> Result<(), DonationError> {
+            let mut campaign = self
+                .campaigns
+                .get(campaign_id)
+                .ok_or(DonationError::CampaignNotFound)?;
+
+            if campaign.released {
+                return Err(DonationError::CampaignReleased);
+            }
+            if campaign.raised < campaign.goal {
+                return Err(DonationError::GoalNotReached);
+            }
+
+            campaign.released = true;
+            self.campaigns.insert(campaign_id, &campaign);
+            self.env()
+                .transfer(campaign.beneficiary, campaign.raised)
+                .map_err(|_| DonationError::TransferFailed)
+        }
+
+        // give the caller's donations back after a campaign missed its goal
+        #[ink(message)]
+        pub fn claim_refund(&mut self, campaign_id: CampaignId) -> Result<(), DonationError> {
+            let campaign = self
+                .campaigns
+                .get(campaign_id)
+                .ok_or(DonationError::CampaignNotFound)?;
+            let caller = self.env().caller();
+
+            if self.env().block_timestamp() < campaign.deadline {
+                return Err(DonationError::CampaignNotEnded);
+            }
+            if campaign.raised >= campaign.goal {
+                return Err(DonationError::GoalReached);
+            }
+
+            let amount = self
+                .contributions
+                .take((campaign_id, caller))
+                .ok_or(DonationError::NothingToRefund)?;
+
+            // refunded donations no longer count towards the donor's total
+            let total = self.donated_by(caller).saturating_sub(amount);
+            self.set_donor_total(caller, total);
+
+            self.env()
+                .transfer(caller, amount)
+                .map_err(|_| DonationError::TransferFailed)
+        }
+
+        #[ink(message)]
+        pub fn get_campaign(&self, campaign_id: CampaignId) -> Option<Campaign> {
+            self.campaigns.get(campaign_id)
+        }
+
+        #[ink(message)]
+        pub fn get_contribution(&self, campaign_id: CampaignId, account: AccountId) -> Balance {
+            self.contributions
+                .get((campaign_id, account))
+                .unwrap_or_default()
+        }
+
+        // pledge `amount` per `interval` out of the transferred deposit, the first
+        // payment is due right away
+        #[ink(message, payable)]
+        pub fn create_pledge(
+            &mut self,
+            amount: Balance,
+            interval: Timestamp,
+        ) -> Result<PledgeId, DonationError> {
+            let deposit = self.env().transferred_value();
+
+            self.check_donation(amount)?;
+            if interval == 0 {
+                return Err(DonationError::ZeroInterval);
+            }
+            if deposit < amount {
+                return Err(DonationError::InsufficientDeposit);
+            }
+
+            let pledge_id = self.next_pledge_id();
+            self.pledges.insert(
+                pledge_id,
+                &Pledge {
+                    donor: self.env().caller(),
+                    amount,
+                    interval,
+                    next_payment: self.env().block_timestamp(),
+                    deposit,
+                },
+            );
+            Ok(pledge_id)
+        }
+
+        // pay the due amount of a pledge to the beneficiaries, callable by anyone
+        #[ink(message)]
+        pub fn execute_pledge(&mut self, pledge_id: PledgeId) -> Result<(), DonationError> {
+            self.ensure_not_paused()?;
+            let mut pledge = self
+                .pledges
+                .get(pledge_id)
+                .ok_or(DonationError::PledgeNotFound)?;
+
+            let now = self.env().block_timestamp();
+            if now < pledge.next_payment {
+                return Err(DonationError::PledgeNotDue);
+            }
+            if pledge.deposit < pledge.amount {
+                return Err(DonationError::InsufficientDeposit);
+            }
+
+            pledge.deposit -= pledge.amount;
+            // missed intervals are skipped rather than paid out back to back
+            let missed = (now - pledge.next_payment) / pledge.interval;
+            pledge.next_payment = pledge
+                .next_payment
+                .saturating_add(pledge.interval.saturating_mul(missed + 1));
+            self.pledges.insert(pledge_id, &pledge);
+
+            self.record_donation(Donation {
+                account: pledge.donor,
+                amount: pledge.amount,
+                campaign: None,
+                token: None,
+                message: None,
+                anonymous: false,
+            });
+            self.add_donor_amount(pledge.donor, pledge.amount);
+
+            for (beneficiary, part) in split_amount(&self.beneficiaries, pledge.amount) {
+                self.env()
+                    .transfer(beneficiary, part)
+                    .map_err(|_| DonationError::TransferFailed)?;
+            }
+            Ok(())
+        }
+
+        // stop a pledge and give the remaining deposit back to the donor
+        #[ink(message)]
+        pub fn cancel_pledge(&mut self, pledge_id: PledgeId) -> Result<(), DonationError> {
+            let pledge = self
+                .pledges
+                .get(pledge_id)
+                .ok_or(DonationError::PledgeNotFound)?;
+
+            if self.env().caller() != pledge.donor {
+                return Err(DonationError::NotPledgeDonor);
+            }
+
+            self.pledges.remove(pledge_id);
+            self.env()
+                .transfer(pledge.donor, pledge.deposit)
+                .map_err(|_| DonationError::TransferFailed)
+        }
+
+        #[ink(message)]
+        pub fn get_pledge(&self, pledge_id: PledgeId) -> Option<Pledge> {
+            self.pledges.get(pledge_id)
+        }
+
+        // lifetime total donated by an account
+        #[ink(message)]
+        pub fn donated_by(&self, account: AccountId) -> u128 {
+            self.donor_totals.get(account).unwrap_or_default()
+        }
+
+        #[ink(message)]
+        pub fn donation_count_of(&self, account: AccountId) -> u32 {
+            self.donor_counts.get(account).unwrap_or_default()
+        }
+
+        // up to `count` biggest donors, highest total first
+        #[ink(message)]
+        pub fn top_donors(&self, count: u32) -> Vec<(AccountId, u128)> {
+            self.leaderboard
+                .iter()
+                .take(count as usize)
+                .cloned()
+                .collect()
+        }
+
+        fn add_donor_amount(&mut self, donor: AccountId, amount: u128) {
+            let count = self.donation_count_of(donor);
+            self.donor_counts.insert(donor, &(count + 1));
+
+            let total = self.donated_by(donor) + amount;
+            self.set_donor_total(donor, total);
+        }
+
+        // store a donor's total and move them to their place on the leaderboard, a donor
+        // whose total drops below the floor leaves the board since someone who was cut
+        // off earlier may have given more
+        fn set_donor_total(&mut self, donor: AccountId, total: u128) {
+            self.donor_totals.insert(donor, &total);
+
+            self.leaderboard.retain(|(account, _)| *account != donor);
+            if total > 0 && total >= self.leaderboard_floor {
+                let position = self
+                    .leaderboard
+                    .iter()
+                    .position(|(_, amount)| *amount < total)
+                    .unwrap_or(self.leaderboard.len());
+                self.leaderboard.insert(position, (donor, total));
+                if self.leaderboard.len() > LEADERBOARD_SIZE {
+                    if let Some((_, cut)) = self.leaderboard.pop() {
+                        self.leaderboard_floor = self.leaderboard_floor.max(cut);
+                    }
+                }
+            }
+        }
+
+        #[ink(message)]
+        pub fn get_receipt(&self, donation_id: DonationId) -> Option<Receipt> {
+            self.receipts.get(donation_id)
+        }
+
+        // receipts of a donor in the order they were minted
+        #[ink(message)]
+        pub fn receipts_of(&self, account: AccountId, offset: u32, limit: u32) -> Vec<Receipt> {
+            let end = offset
+                .saturating_add(limit.min(MAX_PAGE_SIZE))
+                .min(self.balance_of(account));
+
+            (offset..end)
+                .filter_map(|index| self.owned_receipts.get((account, index)))
+                .filter_map(|donation_id| self.receipts.get(donation_id))
+                .collect()
+        }
+
+        // store a donation and mint its receipt to the donor, anonymous donations get
+        // no receipt so they can't be traced back to the donor
+        fn record_donation(&mut self, donation: Donation) -> DonationId {
+            let donation_id = self.next_donation_id();
+            let donor = donation.account;
+            self.donations.insert(donation_id, &donation);
+
+            let beneficiary = donation
+                .campaign
+                .and_then(|campaign_id| self.campaigns.get(campaign_id))
+                .map_or(self.beneficiaries[0].0, |campaign| campaign.beneficiary);
+            let event = Donated {
+                donor: (!donation.anonymous).then_some(donor),
+                beneficiary,
+                donation_id,
+                amount: donation.amount,
+                campaign: donation.campaign,
+                token: donation.token,
+            };
+            if donation.anonymous {
+                self.env().emit_event(event);
+                return donation_id;
+            }
+
+            self.receipts.insert(
+                donation_id,
+                &Receipt {
+                    donation_id,
+                    amount: donation.amount,
+                    timestamp: self.env().block_timestamp(),
+                    campaign: donation.campaign,
+                    token: donation.token,
+                },
+            );
+
+            let count = self.balance_of(donor);
+            self.receipt_owner.insert(donation_id, &donor);
+            self.owned_receipts.insert((donor, count), &donation_id);
+            self.receipt_count.insert(donor, &(count + 1));
+            self.total_receipts += 1;
+
+            self.env().emit_event(event);
+            self.env().emit_event(Transfer {
+                from: None,
+                to: Some(donor),
+                id: donation_id.into(),
+            });
+            donation_id
+        }
+
+        #[inline]
+        pub fn next_pledge_id(&mut self) -> PledgeId {
+            let id = self.pledge_id;
+            self.pledge_id += 1;
+            id
+        }
+
+        #[inline]
+        pub fn next_campaign_id(&mut self) -> CampaignId {
+            let id = self.campaign_id;
+            self.campaign_id += 1;
+            id
+        }
+
+        #[inline]
+        pub fn next_donation_id(&mut self) -> DonationId {
+            let id = self.donation_id;
+            self.donation_id += 1;
+            id
+        }
     }
 
-    impl DonationContract {
-        #[ink(constructor)]
-        pub fn new(beneficiary: AccountId) -> Self {
-            let owner = Self::env().caller();
-            Self {
-                owner,
-                beneficiary,
-                donations: Mapping::default(),
-                donation_id: 1,
-            }
+    // receipts are soulbound, they can't be approved or transferred
+    impl PSP34 for DonationContract {
+        // the contract address identifies the collection
+        #[ink(message)]
+        fn collection_id(&self) -> Id {
+            Id::Bytes(<_ as AsRef<[u8; 32]>>::as_ref(&self.env().account_id()).to_vec())
         }
 
         #[ink(message)]
-        pub fn get_beneficiary(&self) -> Option<AccountId> {
-            Some(self.beneficiary)
+        fn balance_of(&self, owner: AccountId) -> u32 {
+            self.receipt_count.get(owner).unwrap_or_default()
         }
 
         #[ink(message)]
-        pub fn change_beneficiary(&mut self, new_beneficiary: AccountId) {
-            let caller = self.env().caller();
-            assert!(
-                self.owner == caller,
-                "Only owner can change the beneficiary account"
-            );
-            self.beneficiary = new_beneficiary;
+        fn owner_of(&self, id: Id) -> Option<AccountId> {
+            id.donation_id()
+                .and_then(|donation_id| self.receipt_owner.get(donation_id))
         }
 
-        #[ink(message, payable)]
-        pub fn donation(&mut self) {
-            // Account who is donating
-            let caller = self.env().caller();
-            let donation_id = self.next_donation_id();
-
-            // Donation amount
-            let donation_amount = self.env().transferred_value();
-            let mut donated_so_far = self.donations.get(donation_id).unwrap_or_default();
-
-            assert!(donation_amount > 0, "Cannot transfer 0 donation");
-
-            // Total donation amount so far by caller
-            donated_so_far.amount += donation_amount;
-            donated_so_far.account = caller;
-
-            // Insert total donation amount with respect to caller
-            self.donations.insert(donation_id, &donated_so_far);
-
-            // Send donation amount to the beneficiary account
-            self.env()
-                .transfer(self.beneficiary, donation_amount)
-                .unwrap_or_default();
+        #[ink(message)]
+        fn allowance(&self, _owner: AccountId, _operator: AccountId, _id: Option<Id>) -> bool {
+            false
         }
 
         #[ink(message)]
-        pub fn get_donation_amount_by_user(&self, donation_id: DonationId) -> (AccountId, u128) {
-            (
-                self.donations.get(donation_id).unwrap_or_default().account,
-                self.donations.get(donation_id).unwrap_or_default().amount,
-            )
+        fn approve(
+            &mut self,
+            _operator: AccountId,
+            _id: Option<Id>,
+            _approved: bool,
+        ) -> Result<(), PSP34Error> {
+            Err(PSP34Error::Custom(String::from("receipts are soulbound")))
         }
 
         #[ink(message)]
-        pub fn get_donation(&mut self) -> Vec<Donation> {
-            let mut donation: Vec<Donation> = Vec::new();
-            for _donation in 0..self.donation_id {
-                match self.donations.get(_donation) {
-                    Some(value) => donation.push(value),
-                    None => (),
-                }
+        fn transfer(&mut self, _to: AccountId, id: Id, _data: Vec<u8>) -> Result<(), PSP34Error> {
+            if self.owner_of(id).is_none() {
+                return Err(PSP34Error::TokenNotExists);
             }
-            donation
+            Err(PSP34Error::Custom(String::from("receipts are soulbound")))
         }
 
-        #[inline]
-        pub fn next_donation_id(&mut self) -> DonationId {
-            let id = self.donation_id;
-            self.donation_id += 1;
-            id
+        #[ink(message)]
+        fn total_supply(&self) -> Balance {
+            Balance::from(self.total_receipts)
         }
     }
 
@@ -123,6 +1286,56 @@ mod donation_ink {
         [0u8; 32].into()
     }
 
+    // check that a split has distinct recipients whose shares add up to the whole
+    fn validate_beneficiaries(beneficiaries: &[(AccountId, u16)]) -> Result<(), DonationError> {
+        if beneficiaries.is_empty() {
+            return Err(DonationError::NoBeneficiaries);
+        }
+        if beneficiaries.len() > MAX_BENEFICIARIES {
+            return Err(DonationError::TooManyBeneficiaries);
+        }
+
+        let mut total: u32 = 0;
+        for (index, (account, share)) in beneficiaries.iter().enumerate() {
+            if *share == 0 {
+                return Err(DonationError::ZeroShare);
+            }
+            if beneficiaries[..index]
+                .iter()
+                .any(|(other, _)| other == account)
+            {
+                return Err(DonationError::DuplicateBeneficiary);
+            }
+            total += u32::from(*share);
+        }
+        if total != u32::from(TOTAL_SHARES) {
+            return Err(DonationError::InvalidShareTotal);
+        }
+        Ok(())
+    }
+
+    // split an amount by shares rounding down, the remainder goes to the first beneficiary
+    fn split_amount(
+        beneficiaries: &[(AccountId, u16)],
+        amount: Balance,
+    ) -> Vec<(AccountId, Balance)> {
+        let mut parts: Vec<(AccountId, Balance)> = beneficiaries
+            .iter()
+            .map(|(account, share)| {
+                (
+                    *account,
+                    amount * Balance::from(*share) / Balance::from(TOTAL_SHARES),
+                )
+            })
+            .collect();
+
+        let distributed: Balance = parts.iter().map(|(_, part)| part).sum();
+        if let Some((_, first)) = parts.first_mut() {
+            *first += amount - distributed;
+        }
+        parts
+    }
+
     #[cfg(test)]
     mod tests {
         use super::*;
@@ -148,6 +1361,10 @@ mod donation_ink {
                 .expect("cannot get account balance")
         }
 
+        fn set_block_timestamp(timestamp: Timestamp) {
+            ink::env::test::set_block_timestamp::<Environment>(timestamp);
+        }
+
         #[ink::test]
         fn register_works() {
             set_next_caller(default_accounts().alice);
@@ -163,9 +1380,63 @@ mod donation_ink {
 
             assert_eq!(contract.get_beneficiary(), Some(default_accounts().bob));
 
-            contract.change_beneficiary(default_accounts().charlie);
+            assert_eq!(
+                contract.propose_beneficiary(default_accounts().charlie),
+                Ok(())
+            );
+            assert_eq!(contract.get_beneficiary(), Some(default_accounts().bob));
+
+            // only the new beneficiary can accept, and only after the delay
+            assert_eq!(
+                contract.accept_beneficiaries(),
+                Err(DonationError::NotPendingBeneficiary)
+            );
+            set_next_caller(default_accounts().charlie);
+            assert_eq!(
+                contract.accept_beneficiaries(),
+                Err(DonationError::BeneficiaryChangeNotReady)
+            );
+            set_block_timestamp(BENEFICIARY_DELAY);
+            assert_eq!(contract.accept_beneficiaries(), Ok(()));
 
             assert_eq!(contract.get_beneficiary(), Some(default_accounts().charlie));
+            assert_eq!(contract.get_pending_beneficiaries(), None);
+        }
+
+        #[ink::test]
+        fn cancel_beneficiaries_proposal_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::with_delay(accounts.bob, 10);
+            assert_eq!(contract.propose_beneficiary(accounts.charlie), Ok(()));
+            assert_eq!(
+                contract.get_pending_beneficiaries(),
+                Some(PendingBeneficiaries {
+                    beneficiaries: Vec::from([(accounts.charlie, TOTAL_SHARES)]),
+                    effective_at: 10,
+                })
+            );
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.cancel_beneficiaries_proposal(),
+                Err(DonationError::NotOwner)
+            );
+
+            set_next_caller(accounts.alice);
+            assert_eq!(contract.cancel_beneficiaries_proposal(), Ok(()));
+            assert_eq!(
+                contract.cancel_beneficiaries_proposal(),
+                Err(DonationError::NoPendingBeneficiaries)
+            );
+
+            set_block_timestamp(10);
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.accept_beneficiaries(),
+                Err(DonationError::NoPendingBeneficiaries)
+            );
+            assert_eq!(contract.get_beneficiary(), Some(accounts.bob));
         }
 
         #[ink::test]
@@ -184,7 +1455,10 @@ mod donation_ink {
 
             // Donate the balance
             let donate_balance = 90;
-            ink::env::pay_with_call!(contract.donation(), donate_balance);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donation(), donate_balance),
+                Ok(())
+            );
 
             let (account, amount) = contract.get_donation_amount_by_user(donation_id);
             assert_eq!(account, default_accounts().alice);
@@ -194,5 +1468,837 @@ mod donation_ink {
             assert_eq!(get_balance(default_accounts().bob), 90);
             // assert_eq!(get_balance(default_accounts().alice), 100 - 90);
         }
+
+        #[ink::test]
+        fn campaign_release_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            assert_eq!(
+                contract.create_campaign(accounts.django, 100, 0),
+                Err(DonationError::InvalidDeadline)
+            );
+            let campaign_id = contract.create_campaign(accounts.django, 100, 10).unwrap();
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.create_campaign(accounts.charlie, 100, 10),
+                Err(DonationError::NotOwner)
+            );
+
+            set_balance(accounts.django, 0);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 60),
+                Ok(())
+            );
+            assert_eq!(
+                contract.release_campaign(campaign_id),
+                Err(DonationError::GoalNotReached)
+            );
+
+            set_balance(accounts.eve, 100);
+            set_next_caller(accounts.eve);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 40),
+                Ok(())
+            );
+            assert_eq!(contract.get_contribution(campaign_id, accounts.charlie), 60);
+
+            // escrowed funds go to the campaign beneficiary, not the default one
+            assert_eq!(contract.release_campaign(campaign_id), Ok(()));
+            assert_eq!(get_balance(accounts.django), 100);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 10),
+                Err(DonationError::CampaignReleased)
+            );
+            assert_eq!(
+                contract.claim_refund(campaign_id),
+                Err(DonationError::CampaignNotEnded)
+            );
+        }
+
+        #[ink::test]
+        fn campaign_refund_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+            let campaign_id = contract.create_campaign(accounts.django, 100, 10).unwrap();
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 60),
+                Ok(())
+            );
+            assert_eq!(
+                contract.claim_refund(campaign_id),
+                Err(DonationError::CampaignNotEnded)
+            );
+
+            set_block_timestamp(10);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 60),
+                Err(DonationError::CampaignEnded)
+            );
+            assert_eq!(
+                contract.release_campaign(campaign_id),
+                Err(DonationError::GoalNotReached)
+            );
+
+            let balance = get_balance(accounts.charlie);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), balance + 60);
+            assert_eq!(
+                contract.claim_refund(campaign_id),
+                Err(DonationError::NothingToRefund)
+            );
+        }
+
+        #[ink::test]
+        fn donor_totals_and_leaderboard_work() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            for (donor, amount) in [
+                (accounts.charlie, 30),
+                (accounts.django, 50),
+                (accounts.charlie, 40),
+                (accounts.eve, 10),
+            ] {
+                set_balance(donor, 100);
+                set_next_caller(donor);
+                assert_eq!(
+                    ink::env::pay_with_call!(contract.donation(), amount),
+                    Ok(())
+                );
+            }
+
+            assert_eq!(contract.donated_by(accounts.charlie), 70);
+            assert_eq!(contract.donation_count_of(accounts.charlie), 2);
+            assert_eq!(contract.donation_count_of(accounts.frank), 0);
+            assert_eq!(
+                contract.top_donors(2),
+                [(accounts.charlie, 70), (accounts.django, 50)]
+            );
+            assert_eq!(contract.top_donors(10).len(), 3);
+        }
+
+        #[ink::test]
+        fn refund_lowers_donor_total() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+            let campaign_id = contract.create_campaign(accounts.django, 100, 10).unwrap();
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 20), Ok(()));
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 60),
+                Ok(())
+            );
+            assert_eq!(contract.donated_by(accounts.charlie), 80);
+
+            set_block_timestamp(10);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            assert_eq!(contract.donated_by(accounts.charlie), 20);
+            assert_eq!(contract.donation_count_of(accounts.charlie), 2);
+            assert_eq!(contract.top_donors(1), [(accounts.charlie, 20)]);
+        }
+
+        #[ink::test]
+        fn refund_keeps_leaderboard_ranked() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+            let campaign_id = contract
+                .create_campaign(accounts.django, 1_000, 10)
+                .unwrap();
+
+            // donor `i` gives `10 * i`, the two smallest end up cut off the board
+            let donors: Vec<AccountId> = (1..=LEADERBOARD_SIZE as u8 + 1)
+                .map(|i| AccountId::from([100 + i; 32]))
+                .collect();
+            for (i, donor) in donors.iter().enumerate() {
+                set_balance(*donor, 1_000);
+                set_next_caller(*donor);
+                let amount = 10 * (i as u128 + 1);
+                assert_eq!(
+                    ink::env::pay_with_call!(contract.donation(), amount),
+                    Ok(())
+                );
+            }
+            set_balance(accounts.charlie, 1_000);
+            set_next_caller(accounts.charlie);
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 5), Ok(()));
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 200),
+                Ok(())
+            );
+            assert_eq!(contract.top_donors(1), [(accounts.charlie, 205)]);
+            assert!(!contract
+                .top_donors(10)
+                .iter()
+                .any(|(account, _)| *account == donors[1]));
+
+            // charlie drops to 5, below the cut off donor with 20, and leaves the board
+            set_block_timestamp(10);
+            assert_eq!(contract.claim_refund(campaign_id), Ok(()));
+            let top = contract.top_donors(10);
+            assert_eq!(top.len(), LEADERBOARD_SIZE - 1);
+            assert_eq!(top[0], (donors[10], 110));
+            assert_eq!(top[LEADERBOARD_SIZE - 2], (donors[2], 30));
+
+            // a cut off donor giving again takes the free place
+            set_next_caller(donors[1]);
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 1), Ok(()));
+            let top = contract.top_donors(10);
+            assert_eq!(top.len(), LEADERBOARD_SIZE);
+            assert_eq!(top[LEADERBOARD_SIZE - 1], (donors[1], 21));
+        }
+
+        #[ink::test]
+        fn set_beneficiaries_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            for (beneficiaries, error) in [
+                (Vec::new(), DonationError::NoBeneficiaries),
+                (
+                    Vec::from([(accounts.bob, 8_000), (accounts.charlie, 0)]),
+                    DonationError::ZeroShare,
+                ),
+                (
+                    Vec::from([(accounts.bob, 5_000), (accounts.bob, 5_000)]),
+                    DonationError::DuplicateBeneficiary,
+                ),
+                (
+                    Vec::from([(accounts.bob, 8_000), (accounts.charlie, 1_000)]),
+                    DonationError::InvalidShareTotal,
+                ),
+            ] {
+                assert_eq!(contract.propose_beneficiaries(beneficiaries), Err(error));
+            }
+
+            let split = Vec::from([
+                (accounts.bob, 8_000),
+                (accounts.charlie, 1_500),
+                (accounts.django, 500),
+            ]);
+            set_next_caller(accounts.bob);
+            assert_eq!(
+                contract.propose_beneficiaries(split.clone()),
+                Err(DonationError::NotOwner)
+            );
+
+            set_next_caller(accounts.alice);
+            assert_eq!(contract.propose_beneficiaries(split.clone()), Ok(()));
+
+            set_block_timestamp(BENEFICIARY_DELAY);
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.accept_beneficiaries(), Ok(()));
+            assert_eq!(contract.get_beneficiaries(), split);
+            assert_eq!(contract.get_beneficiary(), Some(accounts.bob));
+        }
+
+        #[ink::test]
+        fn donation_is_split() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::with_delay(accounts.bob, 0);
+            assert_eq!(
+                contract.propose_beneficiaries(Vec::from([
+                    (accounts.bob, 8_000),
+                    (accounts.charlie, 1_500),
+                    (accounts.django, 500),
+                ])),
+                Ok(())
+            );
+            set_next_caller(accounts.bob);
+            assert_eq!(contract.accept_beneficiaries(), Ok(()));
+
+            set_balance(accounts.bob, 0);
+            set_balance(accounts.charlie, 0);
+            set_balance(accounts.django, 0);
+            set_balance(accounts.eve, 1_000);
+            set_next_caller(accounts.eve);
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 999), Ok(()));
+
+            // 799.2, 149.85 and 49.95 round down, the dust goes to the first beneficiary
+            assert_eq!(get_balance(accounts.bob), 801);
+            assert_eq!(get_balance(accounts.charlie), 149);
+            assert_eq!(get_balance(accounts.django), 49);
+        }
+
+        #[ink::test]
+        fn donate_token_rejects_zero_amount() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.donate_token(accounts.frank, 0),
+                Err(DonationError::ZeroDonation)
+            );
+            assert_eq!(
+                contract.donated_token_by(accounts.frank, accounts.charlie),
+                0
+            );
+            assert_eq!(contract.get_token_total(accounts.frank), 0);
+        }
+
+        #[ink::test]
+        fn psp22_selectors_match_the_standard() {
+            assert_eq!(
+                PSP22_TRANSFER_SELECTOR,
+                ink::selector_bytes!("PSP22::transfer")
+            );
+            assert_eq!(
+                PSP22_TRANSFER_FROM_SELECTOR,
+                ink::selector_bytes!("PSP22::transfer_from")
+            );
+        }
+
+        #[ink::test]
+        fn pledge_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            set_balance(accounts.bob, 0);
+            set_balance(accounts.charlie, 1_000);
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.create_pledge(100, 30), 50),
+                Err(DonationError::InsufficientDeposit)
+            );
+            let pledge_id = ink::env::pay_with_call!(contract.create_pledge(100, 30), 250).unwrap();
+
+            // anyone can execute a due pledge, once per interval
+            set_next_caller(accounts.django);
+            assert_eq!(contract.execute_pledge(pledge_id), Ok(()));
+            assert_eq!(
+                contract.execute_pledge(pledge_id),
+                Err(DonationError::PledgeNotDue)
+            );
+            set_block_timestamp(30);
+            assert_eq!(contract.execute_pledge(pledge_id), Ok(()));
+            set_block_timestamp(60);
+            assert_eq!(
+                contract.execute_pledge(pledge_id),
+                Err(DonationError::InsufficientDeposit)
+            );
+
+            assert_eq!(get_balance(accounts.bob), 200);
+            assert_eq!(contract.donated_by(accounts.charlie), 200);
+            assert_eq!(contract.donation_count_of(accounts.charlie), 2);
+        }
+
+        #[ink::test]
+        fn cancel_pledge_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            set_balance(accounts.charlie, 1_000);
+            set_next_caller(accounts.charlie);
+            let pledge_id = ink::env::pay_with_call!(contract.create_pledge(100, 30), 250).unwrap();
+            assert_eq!(contract.execute_pledge(pledge_id), Ok(()));
+
+            set_next_caller(accounts.django);
+            assert_eq!(
+                contract.cancel_pledge(pledge_id),
+                Err(DonationError::NotPledgeDonor)
+            );
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(contract.cancel_pledge(pledge_id), Ok(()));
+            assert_eq!(get_balance(accounts.charlie), 1_000 - 100);
+            assert_eq!(contract.get_pledge(pledge_id), None);
+            assert_eq!(
+                contract.execute_pledge(pledge_id),
+                Err(DonationError::PledgeNotFound)
+            );
+        }
+
+        #[ink::test]
+        fn donation_errors_work() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.propose_beneficiary(accounts.charlie),
+                Err(DonationError::NotOwner)
+            );
+            assert_eq!(contract.get_beneficiary(), Some(accounts.bob));
+
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donation(), 0),
+                Err(DonationError::ZeroDonation)
+            );
+            assert_eq!(contract.get_donation_amount_by_user(1), (zero_address(), 0));
+            assert_eq!(contract.donated_by(accounts.charlie), 0);
+        }
+
+        #[ink::test]
+        fn receipts_are_minted_and_soulbound() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+            let campaign_id = contract.create_campaign(accounts.django, 100, 10).unwrap();
+
+            set_next_caller(accounts.charlie);
+            set_block_timestamp(5);
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 20), Ok(()));
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 30),
+                Ok(())
+            );
+
+            assert_eq!(contract.balance_of(accounts.charlie), 2);
+            assert_eq!(contract.total_supply(), 2);
+            assert_eq!(contract.owner_of(Id::U32(2)), Some(accounts.charlie));
+            assert_eq!(contract.owner_of(Id::U64(2)), Some(accounts.charlie));
+            assert_eq!(contract.owner_of(Id::Bytes(vec![2])), None);
+            assert!(matches!(contract.collection_id(), Id::Bytes(bytes) if bytes.len() == 32));
+            assert_eq!(
+                contract.receipts_of(accounts.charlie, 1, 10),
+                [Receipt {
+                    donation_id: 2,
+                    amount: 30,
+                    timestamp: 5,
+                    campaign: Some(campaign_id),
+                    token: None,
+                }]
+            );
+
+            assert!(contract
+                .transfer(accounts.django, Id::U32(1), Vec::new())
+                .is_err());
+            assert!(contract
+                .approve(accounts.django, Some(Id::U32(1)), true)
+                .is_err());
+            assert_eq!(
+                contract.transfer(accounts.django, Id::U32(3), Vec::new()),
+                Err(PSP34Error::TokenNotExists)
+            );
+            assert_eq!(contract.owner_of(Id::U32(1)), Some(accounts.charlie));
+        }
+
+        #[ink::test]
+        fn donor_wall_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                ink::env::pay_with_call!(
+                    contract.donate_with_message(Some(String::from("keep going")), false),
+                    20
+                ),
+                Ok(())
+            );
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_with_message(None, true), 30),
+                Ok(())
+            );
+            assert_eq!(
+                ink::env::pay_with_call!(
+                    contract.donate_with_message(Some("a".repeat(MAX_MESSAGE_LENGTH + 1)), false),
+                    10
+                ),
+                Err(DonationError::MessageTooLong)
+            );
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 40), Ok(()));
+
+            let wall = contract.donor_wall(0, 2);
+            assert_eq!(
+                wall,
+                [
+                    WallEntry {
+                        donation_id: 1,
+                        account: Some(accounts.charlie),
+                        amount: 20,
+                        campaign: None,
+                        token: None,
+                        message: Some(String::from("keep going")),
+                    },
+                    WallEntry {
+                        donation_id: 2,
+                        account: None,
+                        amount: 30,
+                        campaign: None,
+                        token: None,
+                        message: None,
+                    },
+                ]
+            );
+            assert_eq!(contract.donor_wall(2, 10).len(), 1);
+            assert!(contract.donor_wall(3, 10).is_empty());
+            assert_eq!(
+                contract.get_donation_amount_by_user(2),
+                (zero_address(), 30)
+            );
+
+            // the anonymous donation has no receipt and doesn't count for charlie
+            assert_eq!(contract.owner_of(Id::U32(2)), None);
+            assert_eq!(contract.get_receipt(2), None);
+            assert_eq!(contract.balance_of(accounts.charlie), 2);
+            assert_eq!(contract.donated_by(accounts.charlie), 60);
+            assert_eq!(contract.donation_count_of(accounts.charlie), 2);
+            assert_eq!(contract.top_donors(1), [(accounts.charlie, 60)]);
+
+            type Event = <DonationContract as ::ink::reflect::ContractEventBase>::Type;
+            let donors: Vec<Option<AccountId>> = ink::env::test::recorded_events()
+                .filter_map(
+                    |event| match <Event as scale::Decode>::decode(&mut &event.data[..]) {
+                        Ok(Event::Donated(Donated { donor, .. })) => Some(donor),
+                        _ => None,
+                    },
+                )
+                .collect();
+            assert_eq!(
+                donors,
+                [Some(accounts.charlie), None, Some(accounts.charlie)]
+            );
+        }
+
+        #[ink::test]
+        fn matching_pool_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            // match 2:1 up to 150 out of a 500 deposit
+            set_balance(accounts.django, 1_000);
+            set_next_caller(accounts.django);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.create_matching_pool(20_000, 150), 500),
+                Ok(())
+            );
+            assert_eq!(contract.remaining_matching(), 150);
+
+            set_balance(accounts.bob, 0);
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.create_matching_pool(10_000, 100), 100),
+                Err(DonationError::MatchingPoolActive)
+            );
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 50), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 150);
+            assert_eq!(contract.remaining_matching(), 50);
+
+            // the cap limits the next match
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 50), Ok(()));
+            assert_eq!(get_balance(accounts.bob), 250);
+            assert_eq!(contract.remaining_matching(), 0);
+
+            assert_eq!(
+                contract.withdraw_matching_pool(),
+                Err(DonationError::NotSponsor)
+            );
+            set_next_caller(accounts.django);
+            assert_eq!(contract.withdraw_matching_pool(), Ok(()));
+            assert_eq!(get_balance(accounts.django), 1_000 - 150);
+            assert_eq!(contract.get_matching_pool(), None);
+        }
+
+        #[ink::test]
+        fn donation_limits_work() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            assert_eq!(
+                contract.set_donation_limits(50, Some(10)),
+                Err(DonationError::InvalidLimits)
+            );
+            assert_eq!(contract.set_donation_limits(10, Some(50)), Ok(()));
+            assert_eq!(contract.get_donation_limits(), (10, Some(50)));
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                contract.set_donation_limits(0, None),
+                Err(DonationError::NotOwner)
+            );
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donation(), 5),
+                Err(DonationError::DonationTooSmall)
+            );
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donation(), 60),
+                Err(DonationError::DonationTooLarge)
+            );
+            assert_eq!(
+                ink::env::pay_with_call!(contract.create_pledge(60, 30), 60),
+                Err(DonationError::DonationTooLarge)
+            );
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 50), Ok(()));
+        }
+
+        #[ink::test]
+        fn pause_works() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+            let campaign_id = contract.create_campaign(accounts.django, 100, 10).unwrap();
+
+            set_next_caller(accounts.charlie);
+            let pledge_id = ink::env::pay_with_call!(contract.create_pledge(10, 30), 100).unwrap();
+            assert_eq!(contract.pause(), Err(DonationError::NotOwner));
+
+            set_next_caller(accounts.alice);
+            assert_eq!(contract.unpause(), Err(DonationError::ContractNotPaused));
+            assert_eq!(contract.pause(), Ok(()));
+            assert_eq!(contract.pause(), Err(DonationError::ContractPaused));
+            assert!(contract.is_paused());
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donation(), 10),
+                Err(DonationError::ContractPaused)
+            );
+            assert_eq!(
+                ink::env::pay_with_call!(contract.donate_to_campaign(campaign_id), 10),
+                Err(DonationError::ContractPaused)
+            );
+            assert_eq!(
+                contract.donate_token(accounts.frank, 10),
+                Err(DonationError::ContractPaused)
+            );
+            assert_eq!(
+                contract.execute_pledge(pledge_id),
+                Err(DonationError::ContractPaused)
+            );
+            // donors can still get their deposits back
+            assert_eq!(contract.cancel_pledge(pledge_id), Ok(()));
+
+            set_next_caller(accounts.alice);
+            assert_eq!(contract.unpause(), Ok(()));
+            set_next_caller(accounts.charlie);
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 10), Ok(()));
+        }
+
+        #[ink::test]
+        fn donation_events_work() {
+            type Event = <DonationContract as ::ink::reflect::ContractEventBase>::Type;
+
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::with_delay(accounts.bob, 0);
+
+            set_next_caller(accounts.charlie);
+            assert_eq!(ink::env::pay_with_call!(contract.donation(), 20), Ok(()));
+
+            set_next_caller(accounts.alice);
+            assert_eq!(contract.propose_beneficiary(accounts.django), Ok(()));
+            set_next_caller(accounts.django);
+            assert_eq!(contract.accept_beneficiaries(), Ok(()));
+
+            let events: Vec<Event> = ink::env::test::recorded_events()
+                .map(|event| {
+                    <Event as scale::Decode>::decode(&mut &event.data[..])
+                        .expect("encountered invalid contract event data buffer")
+                })
+                .collect();
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::Donated(Donated {
+                    donor,
+                    beneficiary,
+                    donation_id: 1,
+                    amount: 20,
+                    campaign: None,
+                    token: None,
+                }) if *donor == Some(accounts.charlie) && *beneficiary == accounts.bob
+            )));
+            assert!(events.iter().any(|event| matches!(
+                event,
+                Event::BeneficiaryChanged(BeneficiaryChanged { old, new, .. })
+                    if *old == accounts.bob && *new == accounts.django
+            )));
+        }
+
+        #[ink::test]
+        fn missed_pledge_payments_are_skipped() {
+            let accounts = default_accounts();
+            set_next_caller(accounts.alice);
+            let mut contract = DonationContract::new(accounts.bob);
+
+            set_balance(accounts.charlie, 1_000);
+            set_next_caller(accounts.charlie);
+            let pledge_id = ink::env::pay_with_call!(contract.create_pledge(100, 30), 500).unwrap();
+            assert_eq!(contract.execute_pledge(pledge_id), Ok(()));
+
+            // three intervals pass without a keeper, only one payment is due
+            set_block_timestamp(100);
+            assert_eq!(contract.execute_pledge(pledge_id), Ok(()));
+            assert_eq!(
+                contract.execute_pledge(pledge_id),
+                Err(DonationError::PledgeNotDue)
+            );
+            assert_eq!(
+                contract
+                    .get_pledge(pledge_id)
+                    .map(|pledge| pledge.next_payment),
+                Some(120)
+            );
+            assert_eq!(contract.donated_by(accounts.charlie), 200);
+
+            set_block_timestamp(120);
+            assert_eq!(contract.execute_pledge(pledge_id), Ok(()));
+        }
+    }
+
+    /// This is how you'd write end-to-end (E2E) or integration tests for ink! contracts.
+    ///
+    /// When running these you need to make sure that you:
+    /// - Compile the tests with the `e2e-tests` feature flag enabled (`--features e2e-tests`)
+    /// - Are running a Substrate node which contains `pallet-contracts` in the background
+    #[cfg(all(test, feature = "e2e-tests"))]
+    mod e2e_tests {
+        /// Imports all the definitions from the outer scope so we can use them here.
+        use super::*;
+
+        /// A helper function used for calling contract messages.
+        use ink_e2e::build_message;
+
+        /// The PSP22 token donated in the tests.
+        use psp22_mock::{Psp22MockRef, PSP22};
+
+        /// The End-to-End test `Result` type.
+        type E2EResult<T> = std::result::Result<T, Box<dyn std::error::Error>>;
+
+        /// We test that PSP22 donations are pulled in, split between the beneficiaries and
+        /// recorded, and that a failed token transfer reverts the donation.
+        #[ink_e2e::test(additional_contracts = "psp22_mock/Cargo.toml")]
+        async fn donate_token_works(mut client: ink_e2e::Client<C, E>) -> E2EResult<()> {
+            // Given
+            let alice = ink_e2e::account_id(ink_e2e::AccountKeyring::Alice);
+            let bob = ink_e2e::account_id(ink_e2e::AccountKeyring::Bob);
+            let charlie = ink_e2e::account_id(ink_e2e::AccountKeyring::Charlie);
+
+            let constructor = DonationContractRef::with_delay(bob, 0);
+            let donation_account_id = client
+                .instantiate("donation_ink", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let constructor = Psp22MockRef::new(1_000);
+            let token_account_id = client
+                .instantiate("psp22_mock", &ink_e2e::alice(), constructor, 0, None)
+                .await
+                .expect("instantiate failed")
+                .account_id;
+
+            let propose = build_message::<DonationContractRef>(donation_account_id.clone()).call(
+                |donation| {
+                    donation.propose_beneficiaries(Vec::from([(bob, 7_000), (charlie, 3_000)]))
+                },
+            );
+            client
+                .call(&ink_e2e::alice(), propose, 0, None)
+                .await
+                .expect("calling `propose_beneficiaries` failed");
+            let accept = build_message::<DonationContractRef>(donation_account_id.clone())
+                .call(|donation| donation.accept_beneficiaries());
+            client
+                .call(&ink_e2e::bob(), accept, 0, None)
+                .await
+                .expect("calling `accept_beneficiaries` failed");
+
+            let approve = build_message::<Psp22MockRef>(token_account_id.clone())
+                .call(|token| token.approve(donation_account_id.clone(), 300));
+            client
+                .call(&ink_e2e::alice(), approve, 0, None)
+                .await
+                .expect("calling `approve` failed");
+
+            // When
+            let donate = build_message::<DonationContractRef>(donation_account_id.clone())
+                .call(|donation| donation.donate_token(token_account_id.clone(), 200));
+            client
+                .call(&ink_e2e::alice(), donate, 0, None)
+                .await
+                .expect("calling `donate_token` failed");
+
+            // Then
+            for (account, balance) in [
+                (alice, 800),
+                (bob, 140),
+                (charlie, 60),
+                (donation_account_id.clone(), 0),
+            ] {
+                let balance_of = build_message::<Psp22MockRef>(token_account_id.clone())
+                    .call(|token| token.balance_of(account));
+                let balance_of_result = client
+                    .call_dry_run(&ink_e2e::alice(), &balance_of, 0, None)
+                    .await;
+                assert_eq!(balance_of_result.return_value(), balance);
+            }
+
+            let donated_token_by =
+                build_message::<DonationContractRef>(donation_account_id.clone())
+                    .call(|donation| donation.donated_token_by(token_account_id.clone(), alice));
+            let donated_token_by_result = client
+                .call_dry_run(&ink_e2e::alice(), &donated_token_by, 0, None)
+                .await;
+            assert_eq!(donated_token_by_result.return_value(), 200);
+
+            let get_receipt = build_message::<DonationContractRef>(donation_account_id.clone())
+                .call(|donation| donation.get_receipt(1));
+            let receipt = client
+                .call_dry_run(&ink_e2e::alice(), &get_receipt, 0, None)
+                .await
+                .return_value()
+                .expect("no receipt was minted");
+            assert_eq!(receipt.amount, 200);
+            assert_eq!(receipt.token, Some(token_account_id.clone()));
+
+            let owner_of = build_message::<DonationContractRef>(donation_account_id.clone())
+                .call(|donation| donation.owner_of(Id::U32(1)));
+            let owner_of_result = client
+                .call_dry_run(&ink_e2e::alice(), &owner_of, 0, None)
+                .await;
+            assert_eq!(owner_of_result.return_value(), Some(alice));
+
+            // only 100 of the allowance is left, the token returns an error and nothing
+            // of the donation is kept
+            let donate = build_message::<DonationContractRef>(donation_account_id.clone())
+                .call(|donation| donation.donate_token(token_account_id.clone(), 150));
+            let donate_result = client
+                .call_dry_run(&ink_e2e::alice(), &donate, 0, None)
+                .await;
+            assert_eq!(
+                donate_result.return_value(),
+                Err(DonationError::TokenTransferFailed)
+            );
+            assert!(client
+                .call(&ink_e2e::alice(), donate, 0, None)
+                .await
+                .is_err());
+
+            let get_token_total = build_message::<DonationContractRef>(donation_account_id.clone())
+                .call(|donation| donation.get_token_total(token_account_id.clone()));
+            let get_token_total_result = client
+                .call_dry_run(&ink_e2e::alice(), &get_token_total, 0, None)
+                .await;
+            assert_eq!(get_token_total_result.return_value(), 200);
+
+            let get_receipt = build_message::<DonationContractRef>(donation_account_id.clone())
+                .call(|donation| donation.get_receipt(2));
+            let get_receipt_result = client
+                .call_dry_run(&ink_e2e::alice(), &get_receipt, 0, None)
+                .await;
+            assert_eq!(get_receipt_result.return_value(), None);
+
+            Ok(())
+        }
     }
 }